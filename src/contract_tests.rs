@@ -1,9 +1,12 @@
 #![cfg(test)]
 
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info, MOCK_CONTRACT_ADDR,
+};
 
 use cosmwasm_std::{
-    from_binary, to_binary, CosmosMsg, DepsMut, Empty, Response, StdError, WasmMsg,
+    attr, coin, coins, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, DepsMut,
+    Empty, Event, HexBinary, Response, StdError, Uint128, WasmMsg,
 };
 
 use cw721::{
@@ -11,9 +14,19 @@ use cw721::{
     NftInfoResponse, OperatorResponse, OperatorsResponse, OwnerOfResponse,
 };
 use cw_ownable::OwnershipError;
-
+use cw_utils::{Duration, PaymentError};
+use sha2::{Digest, Sha256};
+
+use crate::msg::{
+    BalanceResponse, BlocklistResponse, Cw2981QueryMsg, DenomSolvency, ListingsResponse,
+    MetadataFrozenResponse, MigrateMsg, MintItem, NumOwnersResponse, OfferInfo, OffersResponse,
+    OwnerBalance, OwnersResponse, RoyaltiesInfoResponse, TotalRewardResponse, TransferableResponse,
+    WhitelistEligibilityResponse,
+};
+use crate::state::{Auction, BlockReason, KeeperBounty, PauseConfig, WithdrawConfig};
 use crate::{
-    ContractError, Cw721Contract, ExecuteMsg, Extension, InstantiateMsg, MinterResponse, QueryMsg,
+    entry, ContractError, Cw721Contract, ExecuteMsg, Extension, InstantiateMsg, MinterResponse,
+    QueryMsg,
 };
 
 const MERLIN: &str = "merlin";
@@ -27,6 +40,9 @@ fn setup_contract(deps: DepsMut<'_>) -> Cw721Contract<'static, Extension, Empty,
         symbol: SYMBOL.to_string(),
         minter: MERLIN.to_string(),
         rewards_denom: "aconst".to_string(),
+        base_token_uri: None,
+        max_supply: None,
+        transferable: None,
     };
     let info = mock_info("creator", &[]);
     let res = contract.instantiate(deps, mock_env(), info, msg).unwrap();
@@ -44,6 +60,9 @@ fn proper_instantiation() {
         symbol: SYMBOL.to_string(),
         minter: MERLIN.to_string(),
         rewards_denom: "aconst".to_string(),
+        base_token_uri: None,
+        max_supply: None,
+        transferable: None,
     };
     let info = mock_info("creator", &[]);
 
@@ -847,3 +866,2785 @@ fn query_tokens_by_owner() {
         .unwrap();
     assert_eq!(&by_demeter[1..], &tokens.tokens[..]);
 }
+
+#[test]
+fn solvency_and_insufficient_balance() {
+    let mut deps = mock_dependencies_with_balance(&coins(50, "aconst"));
+    let contract = setup_contract(deps.as_mut());
+
+    let token_id = "petrify".to_string();
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: token_id.clone(),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+        .unwrap();
+
+    contract
+        .add_total_arch_reward(deps.as_mut().storage, 100)
+        .unwrap();
+
+    // contract owes more than it holds
+    let res = contract.solvency(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(
+        res.denoms,
+        vec![DenomSolvency {
+            denom: "aconst".to_string(),
+            balance: Uint128::new(50),
+            owed: Uint128::new(100),
+            surplus: Uint128::zero(),
+            deficit: Uint128::new(50),
+        }]
+    );
+
+    // claims fail with a typed error instead of a bank error
+    let claim_msg = ExecuteMsg::WithdrawTokenRewards {
+        token_id: token_id.clone(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            claim_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientRewardBalance {
+            denom: "aconst".to_string(),
+            available: Uint128::new(50),
+            required: Uint128::new(100),
+        }
+    );
+
+    // once topped up the claim goes through and is no longer owed
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(150, "aconst"));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            claim_msg,
+        )
+        .unwrap();
+
+    let res = contract.solvency(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(res.denoms[0].owed, Uint128::zero());
+    assert_eq!(res.denoms[0].surplus, Uint128::new(150));
+    assert_eq!(res.denoms[0].deficit, Uint128::zero());
+}
+
+#[test]
+fn reward_share_requires_minted_tokens() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    contract
+        .add_total_arch_reward(deps.as_mut().storage, 100)
+        .unwrap();
+
+    // no division by zero on an empty collection
+    let err = contract
+        .token_rewards(deps.as_ref().storage, 0)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoTokensMinted {});
+
+    // the collection-wide total is still readable
+    let res = contract
+        .get_total_arch_rewards(deps.as_ref(), None)
+        .unwrap();
+    assert_eq!(res.total_arch_reward, 100);
+}
+
+#[test]
+fn reward_total_overflow() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    contract
+        .add_total_arch_reward(deps.as_mut().storage, u128::MAX)
+        .unwrap();
+    let err = contract
+        .add_total_arch_reward(deps.as_mut().storage, 1)
+        .unwrap_err();
+    assert_eq!(err, ContractError::RewardOverflow {});
+}
+
+#[test]
+fn claimed_more_than_share_after_supply_grows() {
+    let mut deps = mock_dependencies_with_balance(&coins(1000, "aconst"));
+    let contract = setup_contract(deps.as_mut());
+    let minter = mock_info(MERLIN, &[]);
+
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: "petrify1".to_string(),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+        .unwrap();
+
+    contract
+        .add_total_arch_reward(deps.as_mut().storage, 100)
+        .unwrap();
+
+    let claim_msg = ExecuteMsg::WithdrawTokenRewards {
+        token_id: "petrify1".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            claim_msg.clone(),
+        )
+        .unwrap();
+
+    // a second token halves the share below what was already claimed
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: "petrify2".to_string(),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            claim_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+}
+
+#[test]
+fn zero_value_claims() {
+    let mut deps = mock_dependencies_with_balance(&coins(1000, "aconst"));
+    let contract = setup_contract(deps.as_mut());
+    let minter = mock_info(MERLIN, &[]);
+    let venus = mock_info("venus", &[]);
+
+    for token_id in ["petrify1", "petrify2"] {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: String::from("venus"),
+            token_uri: None,
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+    contract
+        .add_total_arch_reward(deps.as_mut().storage, 100)
+        .unwrap();
+
+    let res = contract
+        .get_total_arch_rewards(deps.as_ref(), Some("petrify1".to_string()))
+        .unwrap();
+    assert_eq!(
+        res,
+        TotalRewardResponse {
+            total_arch_reward: 50,
+            claimable: true,
+        }
+    );
+
+    let claim_msg = ExecuteMsg::WithdrawTokenRewards {
+        token_id: "petrify1".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), venus.clone(), claim_msg.clone())
+        .unwrap();
+
+    // nothing left, so the button should be greyed out and a claim rejected
+    let res = contract
+        .get_total_arch_rewards(deps.as_ref(), Some("petrify1".to_string()))
+        .unwrap();
+    assert_eq!(
+        res,
+        TotalRewardResponse {
+            total_arch_reward: 0,
+            claimable: false,
+        }
+    );
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), venus.clone(), claim_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+
+    // batch claims skip empty tokens and never pay a duplicate twice
+    let batch_msg = ExecuteMsg::BatchWithdrawTokenRewards {
+        token_ids: vec![
+            "petrify1".to_string(),
+            "petrify2".to_string(),
+            "petrify2".to_string(),
+        ],
+    };
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), venus.clone(), batch_msg.clone())
+        .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "venus".to_string(),
+            amount: coins(50, "aconst"),
+        })
+    );
+    assert_eq!(res.attributes[1], attr("token_ids", "petrify2"));
+
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), venus, batch_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+}
+
+#[test]
+fn withdraw_rewards_restrictions() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // only the owner can restrict withdrawals
+    let config_msg = ExecuteMsg::UpdateWithdrawConfig {
+        min_interval: Some(Duration::Height(10)),
+        min_pending_rewards: None,
+        keepers: Some(vec!["keeper".to_string()]),
+        keeper_bounty: None,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            config_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            config_msg,
+        )
+        .unwrap();
+
+    let withdraw_msg = ExecuteMsg::WithdrawRewards {};
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            withdraw_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotKeeper {});
+
+    let keeper = mock_info("keeper", &[]);
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            keeper.clone(),
+            withdraw_msg.clone(),
+        )
+        .unwrap();
+    assert_eq!(1, res.messages.len());
+
+    // too soon after the last withdrawal
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            keeper.clone(),
+            withdraw_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WithdrawTooSoon {
+            next: Expiration::AtHeight(mock_env().block.height + 10)
+        }
+    );
+
+    let mut later = mock_env();
+    later.block.height += 10;
+    contract
+        .execute(deps.as_mut(), later, keeper, withdraw_msg)
+        .unwrap();
+
+    // updates keep the fields they don't set, an empty keeper list opens withdrawals again
+    let bounty = KeeperBounty {
+        bps: 100,
+        cap: Uint128::new(5),
+    };
+    for config_msg in [
+        ExecuteMsg::UpdateWithdrawConfig {
+            min_interval: None,
+            min_pending_rewards: None,
+            keepers: None,
+            keeper_bounty: Some(bounty.clone()),
+        },
+        ExecuteMsg::UpdateWithdrawConfig {
+            min_interval: None,
+            min_pending_rewards: None,
+            keepers: Some(vec![]),
+            keeper_bounty: None,
+        },
+    ] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("creator", &[]),
+                config_msg,
+            )
+            .unwrap();
+    }
+    assert_eq!(
+        contract.withdraw_config.load(&deps.storage).unwrap(),
+        WithdrawConfig {
+            min_interval: Some(Duration::Height(10)),
+            min_pending_rewards: None,
+            keepers: None,
+            keeper_bounty: Some(bounty),
+        }
+    );
+}
+
+#[test]
+fn public_mint() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let buyer = "buyer";
+
+    // disabled until configured
+    let mint_msg = ExecuteMsg::PublicMint {
+        token_uri: None,
+        extension: None,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(buyer, &coins(10, "aconst")),
+            mint_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::PublicMintDisabled {});
+
+    let start_time = mock_env().block.time.plus_seconds(100);
+    let config_msg = ExecuteMsg::SetMintConfig {
+        price: Coin::new(10, "aconst"),
+        per_address_limit: Some(1),
+        start_time: Some(start_time),
+        end_time: None,
+        treasury: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            config_msg,
+        )
+        .unwrap();
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(buyer, &coins(10, "aconst")),
+            mint_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MintNotStarted {});
+
+    let mut env = mock_env();
+    env.block.time = start_time;
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(buyer, &[]),
+            mint_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Payment(PaymentError::NoFunds {}));
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(buyer, &coins(5, "aconst")),
+            mint_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::IncorrectPayment {
+            expected: Uint128::new(10),
+            received: Uint128::new(5),
+        }
+    );
+
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(buyer, &coins(10, "aconst")),
+            mint_msg.clone(),
+        )
+        .unwrap();
+
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, buyer);
+    // without a treasury the proceeds go to the reward pool
+    let total = contract
+        .get_total_arch_rewards(deps.as_ref(), None)
+        .unwrap();
+    assert_eq!(total.total_arch_reward, 10);
+    let count = contract
+        .mint_count(deps.as_ref(), buyer.to_string())
+        .unwrap();
+    assert_eq!(count.count, 1);
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env,
+            mock_info(buyer, &coins(10, "aconst")),
+            mint_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MintLimitReached { limit: 1 });
+}
+
+#[test]
+fn sequential_ids_and_base_token_uri() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: MERLIN.to_string(),
+        rewards_denom: "aconst".to_string(),
+        base_token_uri: Some("ipfs://base/".to_string()),
+        max_supply: None,
+        transferable: None,
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    let minter = mock_info(MERLIN, &[]);
+
+    let mint_next = ExecuteMsg::MintNext {
+        owner: String::from("medusa"),
+        token_uri: None,
+        extension: None,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            mint_next.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Std(StdError::generic_err("Not minter")));
+
+    contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), mint_next.clone())
+        .unwrap();
+    let info = contract.nft_info(deps.as_ref(), "1".to_string()).unwrap();
+    assert_eq!(info.token_uri, Some("ipfs://base/1.json".to_string()));
+
+    // ids taken by explicit mints are skipped
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: "2".to_string(),
+        owner: String::from("medusa"),
+        token_uri: Some("ipfs://custom.json".to_string()),
+        extension: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+        .unwrap();
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_next)
+        .unwrap();
+    let tokens = contract.all_tokens(deps.as_ref(), None, None).unwrap();
+    assert_eq!(tokens.tokens, vec!["1", "2", "3"]);
+
+    // the base uri applies to existing tokens until frozen
+    let owner = mock_info("creator", &[]);
+    let update_msg = ExecuteMsg::UpdateBaseTokenUri {
+        base_token_uri: "ipfs://revealed".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), update_msg.clone())
+        .unwrap();
+    let info = contract.nft_info(deps.as_ref(), "1".to_string()).unwrap();
+    assert_eq!(info.token_uri, Some("ipfs://revealed/1.json".to_string()));
+    let info = contract.nft_info(deps.as_ref(), "2".to_string()).unwrap();
+    assert_eq!(info.token_uri, Some("ipfs://custom.json".to_string()));
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::FreezeBaseTokenUri {},
+        )
+        .unwrap();
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner, update_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::BaseTokenUriFrozen {});
+}
+
+fn merkle_leaf(address: &str, allocation: u32) -> Vec<u8> {
+    Sha256::new()
+        .chain_update(address.as_bytes())
+        .chain_update(allocation.to_be_bytes())
+        .finalize()
+        .to_vec()
+}
+
+fn merkle_hash_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    if a <= b {
+        hasher.update(a);
+        hasher.update(b);
+    } else {
+        hasher.update(b);
+        hasher.update(a);
+    }
+    hasher.finalize().to_vec()
+}
+
+#[test]
+fn whitelist_mint_phase() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let alice_leaf = merkle_leaf("alice", 2);
+    let bob_leaf = merkle_leaf("bob", 1);
+    let root = merkle_hash_pair(&alice_leaf, &bob_leaf);
+    let alice_proof = vec![HexBinary::from(bob_leaf)];
+
+    let start_time = mock_env().block.time;
+    let end_time = start_time.plus_seconds(1000);
+    let phase_msg = ExecuteMsg::SetMintPhase {
+        phase_id: 1,
+        merkle_root: HexBinary::from(root),
+        start_time,
+        end_time,
+        price: Coin::new(5, "aconst"),
+        treasury: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            phase_msg,
+        )
+        .unwrap();
+
+    let res = contract
+        .whitelist_eligibility(
+            deps.as_ref(),
+            mock_env(),
+            "alice".to_string(),
+            2,
+            alice_proof.clone(),
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        WhitelistEligibilityResponse {
+            phase_id: Some(1),
+            eligible: true,
+            remaining: 2,
+        }
+    );
+
+    // a proof for someone else, or for a larger allocation, does not verify
+    let mint_msg = |allocation: u32| ExecuteMsg::WhitelistMint {
+        allocation,
+        proof: alice_proof.clone(),
+        token_uri: None,
+        extension: None,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("carol", &coins(5, "aconst")),
+            mint_msg(2),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMerkleProof {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &coins(5, "aconst")),
+            mint_msg(3),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMerkleProof {});
+
+    for _ in 0..2 {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("alice", &coins(5, "aconst")),
+                mint_msg(2),
+            )
+            .unwrap();
+    }
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "2".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "alice");
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &coins(5, "aconst")),
+            mint_msg(2),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MintLimitReached { limit: 2 });
+
+    let res = contract
+        .whitelist_eligibility(
+            deps.as_ref(),
+            mock_env(),
+            "alice".to_string(),
+            2,
+            alice_proof.clone(),
+        )
+        .unwrap();
+    assert!(!res.eligible);
+    assert_eq!(res.remaining, 0);
+
+    // nothing to mint into once the phase is over
+    let mut env = mock_env();
+    env.block.time = end_time;
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env,
+            mock_info("bob", &coins(5, "aconst")),
+            mint_msg(1),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoActiveMintPhase {});
+}
+
+#[test]
+fn batch_minting() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: MERLIN.to_string(),
+        rewards_denom: "aconst".to_string(),
+        base_token_uri: None,
+        max_supply: Some(2),
+        transferable: None,
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    let minter = mock_info(MERLIN, &[]);
+
+    let item = |token_id: &str, owner: &str| MintItem {
+        token_id: token_id.to_string(),
+        owner: owner.to_string(),
+        token_uri: None,
+        extension: None,
+    };
+
+    // random cannot batch mint
+    let batch_msg = ExecuteMsg::BatchMint {
+        mints: vec![item("1", "medusa"), item("2", "venus")],
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            batch_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Std(StdError::generic_err("Not minter")));
+
+    // the whole batch must fit in the max supply
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            ExecuteMsg::BatchMint {
+                mints: vec![item("1", "medusa"), item("2", "venus"), item("3", "venus")],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MaxSupplyExceeded {});
+
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), batch_msg)
+        .unwrap();
+    assert_eq!(res.events.len(), 2);
+    assert_eq!(
+        res.events[1],
+        Event::new("mint")
+            .add_attribute("owner", "venus")
+            .add_attribute("token_id", "2")
+    );
+    let count = contract.num_tokens(deps.as_ref()).unwrap();
+    assert_eq!(2, count.count);
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "2".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "venus");
+
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: "3".to_string(),
+        owner: String::from("medusa"),
+        token_uri: None,
+        extension: None,
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::MaxSupplyExceeded {});
+}
+
+#[test]
+fn batch_transferring_and_sending_nft() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let minter = mock_info(MERLIN, &[]);
+
+    for (token_id, owner) in [("1", "venus"), ("2", "venus"), ("3", "medusa")] {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    // venus doesn't own token 3, so nothing moves
+    let transfer_msg = ExecuteMsg::BatchTransferNft {
+        recipient: "random".to_string(),
+        token_ids: vec!["3".to_string(), "1".to_string()],
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    let transfer_msg = ExecuteMsg::BatchTransferNft {
+        recipient: "random".to_string(),
+        token_ids: vec!["2".to_string(), "1".to_string(), "2".to_string()],
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DuplicateTokenId {
+            token_id: "2".to_string(),
+        }
+    );
+
+    // tokens move in the given order
+    let transfer_msg = ExecuteMsg::BatchTransferNft {
+        recipient: "random".to_string(),
+        token_ids: vec!["2".to_string(), "1".to_string()],
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "batch_transfer_nft")
+            .add_attribute("sender", "venus")
+            .add_attribute("recipient", "random")
+            .add_attribute("token_ids", "2,1")
+    );
+    let tokens = contract
+        .tokens(deps.as_ref(), "random".to_string(), None, None)
+        .unwrap();
+    assert_eq!(tokens.tokens, vec!["1".to_string(), "2".to_string()]);
+
+    // send delivers one receive message per token, in the given order
+    let msg = to_binary("stake these").unwrap();
+    let target = String::from("vault");
+    let send_msg = ExecuteMsg::BatchSendNft {
+        contract: target.clone(),
+        token_ids: vec!["2".to_string(), "1".to_string()],
+        msg: msg.clone(),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            send_msg,
+        )
+        .unwrap();
+    let expected = ["2", "1"].map(|token_id| {
+        Cw721ReceiveMsg {
+            sender: String::from("random"),
+            token_id: token_id.to_string(),
+            msg: msg.clone(),
+        }
+        .into_cosmos_msg(target.clone())
+        .unwrap()
+    });
+    assert_eq!(
+        res,
+        Response::new()
+            .add_messages(expected)
+            .add_attribute("action", "batch_send_nft")
+            .add_attribute("sender", "random")
+            .add_attribute("recipient", "vault")
+            .add_attribute("token_ids", "2,1")
+    );
+    let tokens = contract.tokens(deps.as_ref(), target, None, None).unwrap();
+    assert_eq!(tokens.tokens.len(), 2);
+}
+
+#[test]
+fn soulbound_collection() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: MERLIN.to_string(),
+        rewards_denom: "aconst".to_string(),
+        base_token_uri: None,
+        max_supply: None,
+        transferable: Some(false),
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    // minting still works
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: "badge".to_string(),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+        .unwrap();
+
+    let owner = mock_info("venus", &[]);
+    let msgs = [
+        ExecuteMsg::TransferNft {
+            recipient: "random".to_string(),
+            token_id: "badge".to_string(),
+        },
+        ExecuteMsg::SendNft {
+            contract: "another_contract".to_string(),
+            token_id: "badge".to_string(),
+            msg: to_binary("hello").unwrap(),
+        },
+        ExecuteMsg::Approve {
+            spender: "random".to_string(),
+            token_id: "badge".to_string(),
+            expires: None,
+        },
+        ExecuteMsg::ApproveAll {
+            operator: "random".to_string(),
+            expires: None,
+        },
+    ];
+    for msg in msgs {
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), owner.clone(), msg)
+            .unwrap_err();
+        assert_eq!(err, ContractError::NonTransferable {});
+    }
+
+    let res: TransferableResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Transferable { token_id: None },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(!res.transferable);
+}
+
+#[test]
+fn locking_single_token() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    for token_id in ["locked", "free"] {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: String::from("venus"),
+            token_uri: None,
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+            .unwrap();
+    }
+
+    // approve before locking, then make sure the approval can't be used
+    let approve_msg = ExecuteMsg::Approve {
+        spender: "random".to_string(),
+        token_id: "locked".to_string(),
+        expires: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            approve_msg,
+        )
+        .unwrap();
+
+    let lock_msg = ExecuteMsg::SetTokenLock {
+        token_id: "locked".to_string(),
+        locked: true,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            lock_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            lock_msg,
+        )
+        .unwrap();
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "random".to_string(),
+        token_id: "locked".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NonTransferable {});
+
+    // revoking is still allowed, and other tokens are unaffected
+    let revoke_msg = ExecuteMsg::Revoke {
+        spender: "random".to_string(),
+        token_id: "locked".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            revoke_msg,
+        )
+        .unwrap();
+    let transfer_free = ExecuteMsg::TransferNft {
+        recipient: "random".to_string(),
+        token_id: "free".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_free,
+        )
+        .unwrap();
+
+    let res: TransferableResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Transferable {
+                    token_id: Some("locked".to_string()),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(!res.transferable);
+
+    let unlock_msg = ExecuteMsg::SetTokenLock {
+        token_id: "locked".to_string(),
+        locked: false,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            unlock_msg,
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+}
+
+#[test]
+fn pausing() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = |token_id: &str| ExecuteMsg::Mint {
+        token_id: token_id.to_string(),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            mint_msg("1"),
+        )
+        .unwrap();
+
+    let pause_msg = ExecuteMsg::SetPause {
+        minting: true,
+        transfers: true,
+        claims: true,
+        withdrawals: true,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            pause_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            pause_msg,
+        )
+        .unwrap();
+
+    let status: PauseConfig = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(status.minting && status.transfers && status.claims && status.withdrawals);
+
+    let paused = |scope: &str| ContractError::Paused {
+        scope: scope.to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            mint_msg("2"),
+        )
+        .unwrap_err();
+    assert_eq!(err, paused("minting"));
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "random".to_string(),
+        token_id: "1".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, paused("transfers"));
+
+    let claim_msg = ExecuteMsg::WithdrawTokenRewards {
+        token_id: "1".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            claim_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, paused("claims"));
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            ExecuteMsg::WithdrawRewards {},
+        )
+        .unwrap_err();
+    assert_eq!(err, paused("withdrawals"));
+
+    // scopes are independent
+    let pause_msg = ExecuteMsg::SetPause {
+        minting: false,
+        transfers: false,
+        claims: true,
+        withdrawals: true,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            pause_msg,
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            mint_msg("2"),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+}
+
+#[test]
+fn blocklist() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = |token_id: &str, owner: &str| ExecuteMsg::Mint {
+        token_id: token_id.to_string(),
+        owner: owner.to_string(),
+        token_uri: None,
+        extension: None,
+    };
+    for (token_id, owner) in [("1", "venus"), ("2", "medusa")] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MERLIN, &[]),
+                mint_msg(token_id, owner),
+            )
+            .unwrap();
+    }
+
+    let block_msg = ExecuteMsg::BlockAddresses {
+        addresses: vec!["medusa".to_string(), "hades".to_string()],
+        reason: "exploit".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            block_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            block_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        res.events[0],
+        Event::new("block_address")
+            .add_attribute("address", "medusa")
+            .add_attribute("reason", "exploit")
+            .add_attribute("owner", "creator")
+    );
+
+    let blocked = |address: &str| ContractError::Blocked {
+        address: address.to_string(),
+    };
+
+    // blocked addresses can't receive, send, mint or claim
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "hades".to_string(),
+        token_id: "1".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, blocked("hades"));
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "venus".to_string(),
+        token_id: "2".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &[]),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, blocked("medusa"));
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            mint_msg("3", "hades"),
+        )
+        .unwrap_err();
+    assert_eq!(err, blocked("hades"));
+
+    let claim_msg = ExecuteMsg::WithdrawTokenRewards {
+        token_id: "2".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &[]),
+            claim_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, blocked("medusa"));
+
+    let res: BlocklistResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Blocklist {
+                    start_after: None,
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.blocked.len(), 1);
+    assert_eq!(res.blocked[0].address, "hades");
+    assert_eq!(res.blocked[0].reason.reason, "exploit");
+    let res: BlocklistResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Blocklist {
+                    start_after: Some("hades".to_string()),
+                    limit: None,
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.blocked.len(), 1);
+    assert_eq!(res.blocked[0].address, "medusa");
+
+    let unblock_msg = ExecuteMsg::UnblockAddresses {
+        addresses: vec!["medusa".to_string()],
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            unblock_msg,
+        )
+        .unwrap();
+    let status: Option<BlockReason> = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::BlockStatus {
+                    address: "medusa".to_string(),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(status, None);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+}
+
+#[test]
+fn updating_and_freezing_metadata() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    for token_id in ["1", "2"] {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: String::from("venus"),
+            token_uri: Some("ipfs://typo".to_string()),
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+            .unwrap();
+    }
+
+    let update_msg = |token_id: &str| ExecuteMsg::UpdateNftInfo {
+        token_id: token_id.to_string(),
+        token_uri: Some("ipfs://fixed".to_string()),
+        extension: None,
+    };
+    // token owners can't change metadata
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            update_msg("1"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    // minter and owner can
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            update_msg("1"),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update_msg("2"),
+        )
+        .unwrap();
+    let info = contract.nft_info(deps.as_ref(), "1".to_string()).unwrap();
+    assert_eq!(info.token_uri, Some("ipfs://fixed".to_string()));
+
+    let frozen = |deps: cosmwasm_std::Deps, token_id: Option<&str>| -> bool {
+        let res: MetadataFrozenResponse = from_binary(
+            &contract
+                .query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::MetadataFrozen {
+                        token_id: token_id.map(String::from),
+                    },
+                )
+                .unwrap(),
+        )
+        .unwrap();
+        res.frozen
+    };
+
+    // freezing a single token
+    let freeze_msg = ExecuteMsg::FreezeMetadata {
+        token_id: Some("1".to_string()),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            freeze_msg,
+        )
+        .unwrap();
+    assert!(frozen(deps.as_ref(), Some("1")));
+    assert!(!frozen(deps.as_ref(), Some("2")));
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            update_msg("1"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MetadataFrozen {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            update_msg("2"),
+        )
+        .unwrap();
+
+    // freezing the collection
+    let freeze_msg = ExecuteMsg::FreezeMetadata { token_id: None };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            freeze_msg.clone(),
+        )
+        .unwrap();
+    assert!(frozen(deps.as_ref(), None));
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update_msg("2"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MetadataFrozen {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            freeze_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MetadataFrozen {});
+}
+
+#[test]
+fn delayed_reveal() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let base_uri = "ipfs://final";
+    let preimage = "s3cret";
+    let provenance_hash = HexBinary::from(Sha256::digest(format!("{base_uri}{preimage}")).to_vec());
+    let reveal_msg = |base_uri: &str| ExecuteMsg::Reveal {
+        base_uri: base_uri.to_string(),
+        provenance_preimage: preimage.to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            reveal_msg(base_uri),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::RevealNotConfigured {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetRevealConfig {
+                placeholder_uri: "ipfs://placeholder.json".to_string(),
+                provenance_hash: HexBinary::from(b"short".to_vec()),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidProvenanceHash {});
+
+    // a token frozen before the reveal keeps its uri
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: "6".to_string(),
+        owner: String::from("venus"),
+        token_uri: Some("ipfs://six.json".to_string()),
+        extension: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+        .unwrap();
+    let freeze_msg = |token_id: &str| ExecuteMsg::FreezeMetadata {
+        token_id: Some(token_id.to_string()),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            freeze_msg("6"),
+        )
+        .unwrap();
+
+    let config_msg = ExecuteMsg::SetRevealConfig {
+        placeholder_uri: "ipfs://placeholder.json".to_string(),
+        provenance_hash,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            config_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            config_msg.clone(),
+        )
+        .unwrap();
+    // the commitment can't be replaced
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            config_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::RevealAlreadyConfigured {});
+
+    // tokens minted with or without the placeholder as their own uri
+    for (token_id, token_uri) in [
+        ("7", None),
+        ("8", Some("ipfs://placeholder.json".to_string())),
+    ] {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: String::from("venus"),
+            token_uri,
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+            .unwrap();
+        let info = contract
+            .nft_info(deps.as_ref(), token_id.to_string())
+            .unwrap();
+        assert_eq!(info.token_uri, Some("ipfs://placeholder.json".to_string()));
+    }
+
+    // the base uri must match the commitment
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            reveal_msg("ipfs://other"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidReveal {});
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            reveal_msg(base_uri),
+        )
+        .unwrap();
+    let info = contract
+        .all_nft_info(deps.as_ref(), mock_env(), "7".to_string(), false)
+        .unwrap();
+    assert_eq!(info.info.token_uri, Some("ipfs://final/7.json".to_string()));
+    let info = contract.nft_info(deps.as_ref(), "8".to_string()).unwrap();
+    assert_eq!(info.token_uri, Some("ipfs://final/8.json".to_string()));
+    let info = contract.nft_info(deps.as_ref(), "6".to_string()).unwrap();
+    assert_eq!(info.token_uri, Some("ipfs://six.json".to_string()));
+
+    // uris set after the reveal are reported as is, and freezing keeps the revealed uri
+    let update_msg = ExecuteMsg::UpdateNftInfo {
+        token_id: "7".to_string(),
+        token_uri: Some("ipfs://fixed/7.json".to_string()),
+        extension: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            update_msg,
+        )
+        .unwrap();
+    let info = contract.nft_info(deps.as_ref(), "7".to_string()).unwrap();
+    assert_eq!(info.token_uri, Some("ipfs://fixed/7.json".to_string()));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MERLIN, &[]),
+            freeze_msg("8"),
+        )
+        .unwrap();
+    let info = contract.nft_info(deps.as_ref(), "8".to_string()).unwrap();
+    assert_eq!(info.token_uri, Some("ipfs://final/8.json".to_string()));
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            reveal_msg(base_uri),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::AlreadyRevealed {});
+}
+
+#[test]
+fn reveal_needs_unfrozen_uris() {
+    let config_msg = ExecuteMsg::SetRevealConfig {
+        placeholder_uri: "ipfs://placeholder.json".to_string(),
+        provenance_hash: HexBinary::from(Sha256::digest(b"ipfs://finals3cret").to_vec()),
+    };
+    for freeze_msg in [
+        ExecuteMsg::FreezeMetadata { token_id: None },
+        ExecuteMsg::FreezeBaseTokenUri {},
+    ] {
+        let mut deps = mock_dependencies();
+        let contract = setup_contract(deps.as_mut());
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("creator", &[]),
+                freeze_msg,
+            )
+            .unwrap();
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("creator", &[]),
+                config_msg.clone(),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::MetadataFrozen {});
+    }
+}
+
+#[test]
+fn royalties_shared_with_holders() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    for token_id in ["1", "2"] {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: String::from("venus"),
+            token_uri: None,
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+            .unwrap();
+    }
+    let royalty_msg = ExecuteMsg::SetDefaultRoyalty {
+        payment_address: "artist".to_string(),
+        share: Decimal::percent(5),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            royalty_msg,
+        )
+        .unwrap();
+
+    let mode_msg = ExecuteMsg::SetRoyaltyMode { holders: true };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            mode_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            mode_msg,
+        )
+        .unwrap();
+
+    // the contract itself is now the royalty recipient
+    let query_msg = QueryMsg::Extension {
+        msg: Cw2981QueryMsg::RoyaltyInfo {
+            token_id: "1".to_string(),
+            sale_price: Uint128::new(1_000),
+        },
+    };
+    let res: RoyaltiesInfoResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), query_msg)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.address, MOCK_CONTRACT_ADDR);
+    assert_eq!(res.royalty_amount, Uint128::new(50));
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("market", &coins(50, "uatom")),
+            ExecuteMsg::ReceiveRoyalty {},
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Payment(PaymentError::MissingDenom("aconst".to_string()))
+    );
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("market", &coins(50, "aconst")),
+            ExecuteMsg::ReceiveRoyalty {},
+        )
+        .unwrap();
+    let res = contract
+        .get_total_arch_rewards(deps.as_ref(), Some("1".to_string()))
+        .unwrap();
+    assert_eq!(res.total_arch_reward, 25);
+    let res = contract
+        .get_total_arch_rewards(deps.as_ref(), None)
+        .unwrap();
+    assert_eq!(res.total_arch_reward, 50);
+
+    // royalties paid with a plain bank send are swept into the pool by anyone
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(80, "aconst"));
+    for swept in ["30", "0"] {
+        let res = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("random", &[]),
+                ExecuteMsg::SweepRewards {},
+            )
+            .unwrap();
+        assert_eq!(res.attributes[1].value, swept);
+    }
+    let res = contract
+        .get_total_arch_rewards(deps.as_ref(), None)
+        .unwrap();
+    assert_eq!(res.total_arch_reward, 80);
+}
+
+#[test]
+fn fixed_price_listings() {
+    // the balance covers the rewards owed plus the attached payment
+    let mut deps = mock_dependencies_with_balance(&coins(1_100, "aconst"));
+    let contract = setup_contract(deps.as_mut());
+
+    for (token_id, owner) in [("1", "venus"), ("2", "venus"), ("3", "medusa")] {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+            .unwrap();
+    }
+    let royalty_msg = ExecuteMsg::SetDefaultRoyalty {
+        payment_address: "artist".to_string(),
+        share: Decimal::percent(5),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            royalty_msg,
+        )
+        .unwrap();
+    contract
+        .add_total_arch_reward(deps.as_mut().storage, 300)
+        .unwrap();
+
+    let list_msg = |token_id: &str, price: u128| ExecuteMsg::ListNft {
+        token_id: token_id.to_string(),
+        price: Uint128::new(price),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &[]),
+            list_msg("1", 1_000),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    for (token_id, owner, price) in [
+        ("1", "venus", 1_000),
+        ("2", "venus", 700),
+        ("3", "medusa", 800),
+    ] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(owner, &[]),
+                list_msg(token_id, price),
+            )
+            .unwrap();
+    }
+
+    let listed = |deps: cosmwasm_std::Deps, msg: QueryMsg<Cw2981QueryMsg>| -> Vec<String> {
+        let res: ListingsResponse =
+            from_binary(&contract.query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.listings.into_iter().map(|l| l.token_id).collect()
+    };
+    assert_eq!(
+        listed(
+            deps.as_ref(),
+            QueryMsg::ListingsByPrice {
+                start_after: None,
+                limit: Some(2),
+            }
+        ),
+        vec!["2", "3"]
+    );
+    assert_eq!(
+        listed(
+            deps.as_ref(),
+            QueryMsg::ListingsByPrice {
+                start_after: Some((Uint128::new(800), "3".to_string())),
+                limit: None,
+            }
+        ),
+        vec!["1"]
+    );
+    assert_eq!(
+        listed(
+            deps.as_ref(),
+            QueryMsg::ListingsBySeller {
+                seller: "venus".to_string(),
+                start_after: Some("1".to_string()),
+                limit: None,
+            }
+        ),
+        vec!["2"]
+    );
+
+    // the exact price must be attached
+    let buy_msg = |token_id: &str| ExecuteMsg::BuyNft {
+        token_id: token_id.to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(900, "aconst")),
+            buy_msg("1"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::IncorrectPayment {
+            expected: Uint128::new(1_000),
+            received: Uint128::new(900),
+        }
+    );
+
+    // royalty to the artist, the rest plus the token's unclaimed rewards to the seller
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(1_000, "aconst")),
+            buy_msg("1"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|sub| sub.msg)
+            .collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "artist".to_string(),
+                amount: coins(50, "aconst"),
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "venus".to_string(),
+                amount: coins(1_050, "aconst"),
+            }),
+        ]
+    );
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "buyer");
+    let res = contract
+        .get_total_arch_rewards(deps.as_ref(), Some("1".to_string()))
+        .unwrap();
+    assert_eq!(res.total_arch_reward, 0);
+
+    // a transfer cancels the seller's listing
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "medusa".to_string(),
+        token_id: "2".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        listed(
+            deps.as_ref(),
+            QueryMsg::Listings {
+                start_after: None,
+                limit: None,
+            }
+        ),
+        vec!["3"]
+    );
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            ExecuteMsg::CancelListing {
+                token_id: "3".to_string(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &[]),
+            ExecuteMsg::CancelListing {
+                token_id: "3".to_string(),
+            },
+        )
+        .unwrap();
+}
+
+#[test]
+fn offers() {
+    // the balance only holds the escrowed offers
+    let mut deps = mock_dependencies_with_balance(&coins(900, "aconst"));
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: "1".to_string(),
+        owner: "venus".to_string(),
+        token_uri: None,
+        extension: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+        .unwrap();
+
+    let offer_msg = |expires: Option<Expiration>| ExecuteMsg::MakeOffer {
+        token_id: "1".to_string(),
+        expires,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &coins(100, "aconst")),
+            offer_msg(None),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidOffer {
+            reason: "owner can't bid on their own token".to_string(),
+        }
+    );
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, "aconst")),
+            offer_msg(Some(Expiration::AtHeight(mock_env().block.height))),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Expired {});
+
+    // a new offer refunds the bidder's previous one
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(300, "aconst")),
+            offer_msg(None),
+        )
+        .unwrap();
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(500, "aconst")),
+            offer_msg(None),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "buyer".to_string(),
+            amount: coins(300, "aconst"),
+        })
+    );
+    let expires = Expiration::AtHeight(mock_env().block.height + 10);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &coins(400, "aconst")),
+            offer_msg(Some(expires)),
+        )
+        .unwrap();
+
+    let mut late_env = mock_env();
+    late_env.block.height += 10;
+    let query_msg = |include_expired: bool| QueryMsg::Offers {
+        token_id: "1".to_string(),
+        include_expired: Some(include_expired),
+        start_after: None,
+        limit: None,
+    };
+    let res: OffersResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), late_env.clone(), query_msg(false))
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.offers,
+        vec![OfferInfo {
+            bidder: "buyer".to_string(),
+            amount: coin(500, "aconst"),
+            expires: Expiration::Never {},
+        }]
+    );
+    let res: OffersResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), late_env.clone(), query_msg(true))
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.offers.len(), 2);
+
+    // escrowed offers can't pay out rewards
+    let res = contract.solvency(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(res.denoms[0].owed, Uint128::new(900));
+    assert_eq!(res.denoms[0].surplus, Uint128::zero());
+
+    let accept_msg = |bidder: &str| ExecuteMsg::AcceptOffer {
+        token_id: "1".to_string(),
+        bidder: bidder.to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("broker", &[]),
+            accept_msg("buyer"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    // an approved operator can accept on the owner's behalf
+    let approve_msg = ExecuteMsg::Approve {
+        spender: "broker".to_string(),
+        token_id: "1".to_string(),
+        expires: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            approve_msg,
+        )
+        .unwrap();
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("broker", &[]),
+            accept_msg("buyer"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "venus".to_string(),
+            amount: coins(500, "aconst"),
+        })
+    );
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "buyer");
+
+    // an expired offer can't be accepted, only cancelled
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            late_env,
+            mock_info("buyer", &[]),
+            accept_msg("medusa"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Expired {});
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &[]),
+            ExecuteMsg::CancelOffer {
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "medusa".to_string(),
+            amount: coins(400, "aconst"),
+        })
+    );
+    assert_eq!(
+        contract.escrow.load(&deps.storage).unwrap(),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn english_auctions() {
+    let mut deps = mock_dependencies_with_balance(&coins(1_000, "aconst"));
+    let contract = setup_contract(deps.as_mut());
+
+    for token_id in ["1", "2"] {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: "venus".to_string(),
+            token_uri: None,
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+            .unwrap();
+    }
+
+    let start_msg = |token_id: &str, min_increment: u128| ExecuteMsg::StartAuction {
+        token_id: token_id.to_string(),
+        reserve: Uint128::new(100),
+        duration: Duration::Time(3600),
+        min_increment: Uint128::new(min_increment),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            start_msg("1", 0),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidAuction {
+            reason: "min increment must not be zero".to_string(),
+        }
+    );
+    for token_id in ["1", "2"] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("venus", &[]),
+                start_msg(token_id, 10),
+            )
+            .unwrap();
+    }
+
+    // the contract holds the token while bids are open, it still counts for the seller
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, MOCK_CONTRACT_ADDR);
+    let balance = |deps: cosmwasm_std::Deps, owner: &str| {
+        contract
+            .balance(deps.storage, &Addr::unchecked(owner))
+            .unwrap()
+    };
+    assert_eq!(balance(deps.as_ref(), "venus"), 2);
+    assert_eq!(balance(deps.as_ref(), MOCK_CONTRACT_ADDR), 0);
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "medusa".to_string(),
+        token_id: "1".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    let bid_msg = ExecuteMsg::Bid {
+        token_id: "1".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(90, "aconst")),
+            bid_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidAuction {
+            reason: "bid must be at least 100".to_string(),
+        }
+    );
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, "aconst")),
+            bid_msg.clone(),
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &coins(105, "aconst")),
+            bid_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidAuction {
+            reason: "bid must be at least 110".to_string(),
+        }
+    );
+    // outbidding refunds the previous bidder
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &coins(110, "aconst")),
+            bid_msg.clone(),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "buyer".to_string(),
+            amount: coins(100, "aconst"),
+        })
+    );
+
+    let settle_msg = |token_id: &str| ExecuteMsg::SettleAuction {
+        token_id: token_id.to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            settle_msg("1"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidAuction {
+            reason: "auction has not ended".to_string(),
+        }
+    );
+
+    let mut late_env = mock_env();
+    late_env.block.time = late_env.block.time.plus_seconds(3600);
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            late_env.clone(),
+            mock_info("buyer", &coins(200, "aconst")),
+            bid_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Expired {});
+
+    // the highest bidder gets the token and the seller the winning bid
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            late_env.clone(),
+            mock_info("random", &[]),
+            settle_msg("1"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "venus".to_string(),
+            amount: coins(110, "aconst"),
+        })
+    );
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "medusa");
+    assert_eq!(balance(deps.as_ref(), "venus"), 1);
+    assert_eq!(balance(deps.as_ref(), "medusa"), 1);
+    assert_eq!(
+        contract.escrow.load(&deps.storage).unwrap(),
+        Uint128::zero()
+    );
+    let auction: Option<Auction> = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Auction {
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(auction, None);
+
+    // without bids the token goes back to the seller
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            late_env,
+            mock_info("random", &[]),
+            settle_msg("2"),
+        )
+        .unwrap();
+    assert!(res.messages.is_empty());
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "2".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "venus");
+    assert_eq!(balance(deps.as_ref(), "venus"), 1);
+}
+
+#[test]
+fn auction_settlement_fallback() {
+    let mut deps = mock_dependencies_with_balance(&coins(1_000, "aconst"));
+    let contract = setup_contract(deps.as_mut());
+
+    for (token_id, bidder) in [("1", "medusa"), ("2", "buyer"), ("3", "buyer")] {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: "venus".to_string(),
+            token_uri: None,
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+            .unwrap();
+        let start_msg = ExecuteMsg::StartAuction {
+            token_id: token_id.to_string(),
+            reserve: Uint128::new(100),
+            duration: Duration::Time(3600),
+            min_increment: Uint128::new(10),
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("venus", &[]),
+                start_msg,
+            )
+            .unwrap();
+        let bid_msg = ExecuteMsg::Bid {
+            token_id: token_id.to_string(),
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(bidder, &coins(100, "aconst")),
+                bid_msg,
+            )
+            .unwrap();
+    }
+
+    // the winner is blocked before the end
+    let block_msg = ExecuteMsg::BlockAddresses {
+        addresses: vec!["medusa".to_string()],
+        reason: "exploit".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            block_msg,
+        )
+        .unwrap();
+
+    let mut late_env = mock_env();
+    late_env.block.time = late_env.block.time.plus_seconds(3600);
+    let settle_msg = |token_id: &str| ExecuteMsg::SettleAuction {
+        token_id: token_id.to_string(),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            late_env.clone(),
+            mock_info("random", &[]),
+            settle_msg("1"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "medusa".to_string(),
+            amount: coins(100, "aconst"),
+        })
+    );
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "venus");
+
+    // a locked token goes back to the seller too
+    let lock_msg = ExecuteMsg::SetTokenLock {
+        token_id: "3".to_string(),
+        locked: true,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            lock_msg,
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            late_env.clone(),
+            mock_info("random", &[]),
+            settle_msg("3"),
+        )
+        .unwrap();
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "3".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "venus");
+
+    // paused transfers only delay the settlement
+    let pause_msg = |transfers: bool| ExecuteMsg::SetPause {
+        minting: false,
+        transfers,
+        claims: true,
+        withdrawals: false,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            pause_msg(true),
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            late_env.clone(),
+            mock_info("random", &[]),
+            settle_msg("2"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Paused {
+            scope: "transfers".to_string(),
+        }
+    );
+
+    // rewards accrued in escrow go with the token, so paused claims and a balance short of
+    // them don't hold the sale up
+    contract
+        .add_total_arch_reward(deps.as_mut().storage, 300)
+        .unwrap();
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(100, "aconst"));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            pause_msg(false),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            late_env,
+            mock_info("random", &[]),
+            settle_msg("2"),
+        )
+        .unwrap();
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "2".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "buyer");
+    let res = contract
+        .get_total_arch_rewards(deps.as_ref(), Some("2".to_string()))
+        .unwrap();
+    assert_eq!(res.total_arch_reward, 100);
+    assert_eq!(
+        contract.escrow.load(&deps.storage).unwrap(),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn holder_balances() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    for (token_id, owner) in [("1", "venus"), ("2", "venus"), ("3", "medusa")] {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MERLIN, &[]), mint_msg)
+            .unwrap();
+    }
+
+    let balance = |deps: cosmwasm_std::Deps, owner: &str| -> u64 {
+        let query_msg = QueryMsg::Balance {
+            owner: owner.to_string(),
+        };
+        let res: BalanceResponse =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.balance
+    };
+    let num_owners = |deps: cosmwasm_std::Deps| -> u64 {
+        let res: NumOwnersResponse = from_binary(
+            &contract
+                .query(deps, mock_env(), QueryMsg::NumOwners {})
+                .unwrap(),
+        )
+        .unwrap();
+        res.count
+    };
+    assert_eq!(balance(deps.as_ref(), "venus"), 2);
+    assert_eq!(balance(deps.as_ref(), "medusa"), 1);
+    assert_eq!(num_owners(deps.as_ref()), 2);
+
+    // an address giving away its last token is no longer a holder
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "venus".to_string(),
+        token_id: "3".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(balance(deps.as_ref(), "venus"), 3);
+    assert_eq!(balance(deps.as_ref(), "medusa"), 0);
+    assert_eq!(num_owners(deps.as_ref()), 1);
+
+    let transfer_msg = ExecuteMsg::BatchTransferNft {
+        recipient: "ceres".to_string(),
+        token_ids: vec!["1".to_string(), "2".to_string()],
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(num_owners(deps.as_ref()), 2);
+
+    let res: OwnersResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::AllOwners {
+                    start_after: None,
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.owners,
+        vec![OwnerBalance {
+            owner: "ceres".to_string(),
+            balance: 2,
+        }]
+    );
+    let res: OwnersResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::AllOwners {
+                    start_after: Some("ceres".to_string()),
+                    limit: None,
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.owners,
+        vec![OwnerBalance {
+            owner: "venus".to_string(),
+            balance: 1,
+        }]
+    );
+
+    // tokens minted before balances were tracked can't move until migrating counts them
+    contract
+        .balances
+        .remove(&mut deps.storage, &Addr::unchecked("ceres"));
+    contract
+        .balances
+        .remove(&mut deps.storage, &Addr::unchecked("venus"));
+    contract.num_owners.remove(&mut deps.storage);
+    cw2::set_contract_version(&mut deps.storage, crate::CONTRACT_NAME, "0.17.0").unwrap();
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "medusa".to_string(),
+        token_id: "1".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("ceres", &[]),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+
+    entry::migrate(deps.as_mut(), mock_env(), MigrateMsg::Migrate {}).unwrap();
+    assert_eq!(balance(deps.as_ref(), "ceres"), 2);
+    assert_eq!(balance(deps.as_ref(), "venus"), 1);
+    assert_eq!(num_owners(deps.as_ref()), 2);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("ceres", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(num_owners(deps.as_ref()), 3);
+}
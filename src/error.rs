@@ -1,5 +1,6 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use cw_ownable::OwnershipError;
+use cw_utils::{Expiration, PaymentError};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -13,6 +14,9 @@ pub enum ContractError {
     #[error(transparent)]
     Version(#[from] cw2::VersionError),
 
+    #[error(transparent)]
+    Payment(#[from] PaymentError),
+
     #[error("token_id already claimed")]
     Claimed {},
 
@@ -28,6 +32,109 @@ pub enum ContractError {
     #[error("Max supply exceeded")]
     MaxSupplyExceeded {},
 
-    #[error("Royalty percentage must be between 0 and 100")]
+    #[error("Token {token_id} is listed more than once")]
+    DuplicateTokenId { token_id: String },
+
+    #[error("Royalty exceeds the maximum allowed royalty")]
     InvalidRoyaltyPercentage,
+
+    #[error("No tokens have been minted")]
+    NoTokensMinted {},
+
+    #[error("No rewards available to claim")]
+    NothingToClaim {},
+
+    #[error("Reward amount overflow")]
+    RewardOverflow {},
+
+    #[error("Only allowlisted keepers can trigger reward withdrawals")]
+    NotKeeper {},
+
+    #[error("Rewards were withdrawn recently, next withdrawal allowed at {next}")]
+    WithdrawTooSoon { next: Expiration },
+
+    #[error("Pending rewards {pending} are below the withdrawal threshold {threshold}")]
+    BelowWithdrawThreshold {
+        pending: Uint128,
+        threshold: Uint128,
+    },
+
+    #[error("Keeper bounty must be at most 10000 bps")]
+    InvalidKeeperBounty {},
+
+    #[error("Base token uri is frozen")]
+    BaseTokenUriFrozen {},
+
+    #[error("Invalid mint config: {reason}")]
+    InvalidMintConfig { reason: String },
+
+    #[error("No allowlist mint phase is active")]
+    NoActiveMintPhase {},
+
+    #[error("Invalid Merkle proof")]
+    InvalidMerkleProof {},
+
+    #[error("Public mint is not enabled")]
+    PublicMintDisabled {},
+
+    #[error("Public mint has not started yet")]
+    MintNotStarted {},
+
+    #[error("Public mint has ended")]
+    MintEnded {},
+
+    #[error("Address already minted the maximum of {limit} tokens")]
+    MintLimitReached { limit: u32 },
+
+    #[error("Incorrect payment: expected {expected}, received {received}")]
+    IncorrectPayment {
+        expected: Uint128,
+        received: Uint128,
+    },
+
+    #[error("Insufficient {denom} reward balance: available {available}, required {required}")]
+    InsufficientRewardBalance {
+        denom: String,
+        available: Uint128,
+        required: Uint128,
+    },
+
+    #[error("Token is non-transferable")]
+    NonTransferable {},
+
+    #[error("{scope} is paused")]
+    Paused { scope: String },
+
+    #[error("Address {address} is blocked")]
+    Blocked { address: String },
+
+    #[error("Invalid royalty splits: {reason}")]
+    InvalidRoyaltySplits { reason: String },
+
+    #[error("Invalid listing: {reason}")]
+    InvalidListing { reason: String },
+
+    #[error("Invalid offer: {reason}")]
+    InvalidOffer { reason: String },
+
+    #[error("Invalid auction: {reason}")]
+    InvalidAuction { reason: String },
+
+    #[error("Metadata is frozen")]
+    MetadataFrozen {},
+
+    #[error("Collection is already revealed")]
+    AlreadyRevealed {},
+
+    #[error("Base uri and preimage don't match the provenance hash")]
+    InvalidReveal {},
+
+    #[error("Provenance hash must be a sha256 hash")]
+    InvalidProvenanceHash {},
+
+    #[error("No reveal is configured")]
+    RevealNotConfigured {},
+
+    #[error("Reveal is already configured")]
+    RevealAlreadyConfigured {},
 }
@@ -1,23 +1,31 @@
-use archway_bindings::ArchwayMsg;
+use archway_bindings::types::rewards::RewardsRecordsResponse;
+use archway_bindings::{ArchwayMsg, ArchwayQuery, PageRequest};
 use cw_ownable::OwnershipError;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::HashSet;
 
 use cosmwasm_std::{
-    coins, BankMsg, Binary, CustomMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError,
-    StdResult, SubMsg,
+    coin, coins, Addr, BankMsg, Binary, Coin, CustomMsg, Decimal, Deps, DepsMut, Env, Event,
+    HexBinary, MessageInfo, QuerierWrapper, QueryRequest, Response, StdError, StdResult, Storage,
+    SubMsg, Timestamp, Uint128,
 };
 
 use cw721::{ContractInfoResponse, Cw721ReceiveMsg, Expiration};
+use cw_utils::{must_pay, nonpayable, Duration};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MintItem};
+use crate::state::{
+    token_uri_from_base, Approval, Auction, AuctionBid, BlockReason, Cw721Contract, KeeperBounty,
+    Listing, MintConfig, MintPhase, Offer, PauseConfig, RevealConfig, RoyaltyExtension,
+    RoyaltyInfo, TokenInfo, MAX_ROYALTY_BPS,
+};
 use crate::REWARDS_WITHDRAW_REPLY;
 
 impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + RoyaltyExtension,
     C: CustomMsg,
     E: CustomMsg,
     Q: CustomMsg,
@@ -39,6 +47,15 @@ where
         self.minter
             .save(deps.storage, &deps.api.addr_validate(&msg.minter)?)?;
         self.rewards_denom.save(deps.storage, &msg.rewards_denom)?;
+        if let Some(max_supply) = msg.max_supply {
+            self.max_supply.save(deps.storage, &max_supply)?;
+        }
+        if let Some(base_token_uri) = msg.base_token_uri {
+            self.base_token_uri.save(deps.storage, &base_token_uri)?;
+        }
+        if let Some(transferable) = msg.transferable {
+            self.transferable.save(deps.storage, &transferable)?;
+        }
 
         cw_ownable::initialize_owner(deps.storage, deps.api, Some(&owner.as_str()))?;
 
@@ -80,11 +97,158 @@ where
                 token_id,
                 msg,
             } => self.send_nft(deps, env, info, contract, token_id, msg),
+            ExecuteMsg::BatchTransferNft {
+                recipient,
+                token_ids,
+            } => self.batch_transfer_nft(deps, env, info, recipient, token_ids),
+            ExecuteMsg::BatchSendNft {
+                contract,
+                token_ids,
+                msg,
+            } => self.batch_send_nft(deps, env, info, contract, token_ids, msg),
+            ExecuteMsg::ListNft { token_id, price } => self.list_nft(deps, info, token_id, price),
+            ExecuteMsg::CancelListing { token_id } => self.cancel_listing(deps, info, token_id),
+            ExecuteMsg::BuyNft { token_id } => self.buy_nft(deps, env, info, token_id),
+            ExecuteMsg::MakeOffer { token_id, expires } => {
+                self.make_offer(deps, env, info, token_id, expires)
+            }
+            ExecuteMsg::AcceptOffer { token_id, bidder } => {
+                self.accept_offer(deps, env, info, token_id, bidder)
+            }
+            ExecuteMsg::CancelOffer { token_id } => self.cancel_offer(deps, info, token_id),
+            ExecuteMsg::StartAuction {
+                token_id,
+                reserve,
+                duration,
+                min_increment,
+            } => self.start_auction(deps, env, info, token_id, reserve, duration, min_increment),
+            ExecuteMsg::Bid { token_id } => self.bid(deps, env, info, token_id),
+            ExecuteMsg::SettleAuction { token_id } => self.settle_auction(deps, env, token_id),
             ExecuteMsg::Burn { token_id } => self.burn(deps, env, info, token_id),
-            ExecuteMsg::WithdrawRewards {} => self.withdraw_rewards(),
+            ExecuteMsg::BatchMint { mints } => self.batch_mint(deps, info, mints),
+            ExecuteMsg::MintNext {
+                owner,
+                token_uri,
+                extension,
+            } => self.mint_next(deps, info, owner, token_uri, extension),
+            ExecuteMsg::UpdateNftInfo {
+                token_id,
+                token_uri,
+                extension,
+            } => self.update_nft_info(deps, info, token_id, token_uri, extension),
+            ExecuteMsg::FreezeMetadata { token_id } => self.freeze_metadata(deps, info, token_id),
+            ExecuteMsg::SetDefaultRoyalty {
+                payment_address,
+                share,
+            } => self.set_default_royalty(deps, info, payment_address, share),
+            ExecuteMsg::RemoveDefaultRoyalty {} => self.remove_default_royalty(deps, info),
+            ExecuteMsg::SetRoyaltyMode { holders } => self.set_royalty_mode(deps, info, holders),
+            ExecuteMsg::ReceiveRoyalty {} => self.receive_royalty(deps, info),
+            ExecuteMsg::SweepRewards {} => self.sweep_rewards(deps, env),
+            ExecuteMsg::UpdateMaxRoyalty { max_royalty_bps } => {
+                self.update_max_royalty(deps, info, max_royalty_bps)
+            }
+            ExecuteMsg::SetRevealConfig {
+                placeholder_uri,
+                provenance_hash,
+            } => self.set_reveal_config(deps, info, placeholder_uri, provenance_hash),
+            ExecuteMsg::Reveal {
+                base_uri,
+                provenance_preimage,
+            } => self.reveal(deps, info, base_uri, provenance_preimage),
+            ExecuteMsg::UpdateBaseTokenUri { base_token_uri } => {
+                self.update_base_token_uri(deps, info, base_token_uri)
+            }
+            ExecuteMsg::FreezeBaseTokenUri {} => self.freeze_base_token_uri(deps, info),
+            ExecuteMsg::SetPause {
+                minting,
+                transfers,
+                claims,
+                withdrawals,
+            } => self.set_pause(
+                deps,
+                info,
+                PauseConfig {
+                    minting,
+                    transfers,
+                    claims,
+                    withdrawals,
+                },
+            ),
+            ExecuteMsg::BlockAddresses { addresses, reason } => {
+                self.block_addresses(deps, env, info, addresses, reason)
+            }
+            ExecuteMsg::UnblockAddresses { addresses } => {
+                self.unblock_addresses(deps, info, addresses)
+            }
+            ExecuteMsg::SetTokenLock { token_id, locked } => {
+                self.set_token_lock(deps, info, token_id, locked)
+            }
+            ExecuteMsg::PublicMint {
+                token_uri,
+                extension,
+            } => self.public_mint(deps, env, info, token_uri, extension),
+            ExecuteMsg::WhitelistMint {
+                allocation,
+                proof,
+                token_uri,
+                extension,
+            } => self.whitelist_mint(deps, env, info, allocation, proof, token_uri, extension),
+            ExecuteMsg::SetMintPhase {
+                phase_id,
+                merkle_root,
+                start_time,
+                end_time,
+                price,
+                treasury,
+            } => self.set_mint_phase(
+                deps,
+                info,
+                phase_id,
+                merkle_root,
+                start_time,
+                end_time,
+                price,
+                treasury,
+            ),
+            ExecuteMsg::RemoveMintPhase { phase_id } => {
+                self.remove_mint_phase(deps, info, phase_id)
+            }
+            ExecuteMsg::SetMintConfig {
+                price,
+                per_address_limit,
+                start_time,
+                end_time,
+                treasury,
+            } => self.set_mint_config(
+                deps,
+                info,
+                price,
+                per_address_limit,
+                start_time,
+                end_time,
+                treasury,
+            ),
+            ExecuteMsg::WithdrawRewards {} => self.withdraw_rewards(deps, env, info),
+            ExecuteMsg::UpdateWithdrawConfig {
+                min_interval,
+                min_pending_rewards,
+                keepers,
+                keeper_bounty,
+            } => self.update_withdraw_config(
+                deps,
+                info,
+                min_interval,
+                min_pending_rewards,
+                keepers,
+                keeper_bounty,
+            ),
             ExecuteMsg::WithdrawTokenRewards { token_id } => {
                 self.withdraw_token_rewards(deps, env, info, token_id)
             }
+            ExecuteMsg::BatchWithdrawTokenRewards { token_ids } => {
+                self.batch_withdraw_token_rewards(deps, env, info, token_ids)
+            }
             ExecuteMsg::UpdateOwnership(action) => Self::update_ownership(deps, env, info, action),
             ExecuteMsg::UpdateMinter { minter } => {
                 Self::update_minter(&self, deps, env, info, minter)
@@ -96,7 +260,7 @@ where
 // TODO pull this into some sort of trait extension??
 impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + RoyaltyExtension,
     C: CustomMsg,
     E: CustomMsg,
     Q: CustomMsg,
@@ -110,254 +274,1810 @@ where
         token_uri: Option<String>,
         extension: T,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
-        let minter = self.minter.load(deps.storage)?;
+        self.assert_can_mint(deps.storage, &info.sender, 1)?;
+        self._mint(deps, info, token_id, owner, token_uri, extension)
+    }
 
-        if minter != info.sender {
-            return Err(ContractError::Std(StdError::generic_err("Not minter")));
+    pub fn batch_mint(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        mints: Vec<MintItem<T>>,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self.assert_can_mint(deps.storage, &info.sender, mints.len() as u64)?;
+
+        let mut events = Vec::with_capacity(mints.len());
+        for item in mints {
+            let owner = deps.api.addr_validate(&item.owner)?;
+            self._mint_token(
+                deps.storage,
+                &item.token_id,
+                owner,
+                item.token_uri,
+                item.extension,
+            )?;
+            events.push(
+                Event::new("mint")
+                    .add_attribute("owner", item.owner)
+                    .add_attribute("token_id", item.token_id),
+            );
         }
-        let token = TokenInfo {
-            owner: deps.api.addr_validate(&owner)?,
-            approvals: vec![],
-            token_uri,
-            reward_claimed: 0,
-            extension,
-        };
 
-        self.tokens
-            .update(deps.storage, &token_id.to_string(), |old| match old {
-                Some(_) => Err(ContractError::Claimed {}),
-                None => Ok(token),
-            })?;
+        Ok(Response::new()
+            .add_attribute("action", "batch_mint")
+            .add_attribute("minter", info.sender)
+            .add_attribute("count", events.len().to_string())
+            .add_events(events))
+    }
+
+    pub fn mint_next(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        owner: String,
+        token_uri: Option<String>,
+        extension: T,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self.assert_can_mint(deps.storage, &info.sender, 1)?;
+        let token_id = self.next_token_id(deps.storage)?;
+        self._mint(deps, info, token_id, owner, token_uri, extension)
+    }
+
+    pub fn update_nft_info(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        token_uri: Option<String>,
+        extension: T,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self.assert_minter_or_owner(deps.storage, &info.sender)?;
+        if self.is_metadata_frozen(deps.storage, Some(&token_id))? {
+            return Err(ContractError::MetadataFrozen {});
+        }
 
-        self.increment_tokens(deps.storage)?;
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        let had_royalty = token.extension.has_payable_royalty();
+        self.remove_token_traits(deps.storage, &token_id, &token.extension)?;
+        token.token_uri = token_uri;
+        token.extension = extension;
+        // a token updated after the reveal keeps the uri it reports from now on
+        if let Some(base) = self
+            .reveal
+            .may_load(deps.storage)?
+            .and_then(|reveal| reveal.revealed_base_uri)
+        {
+            token.token_uri = token
+                .token_uri
+                .or_else(|| Some(token_uri_from_base(&base, &token_id)));
+            self.pinned_token_uris
+                .save(deps.storage, &token_id, &true)?;
+        }
+        self.tokens.save(deps.storage, &token_id, &token)?;
+        self.add_token_traits(deps.storage, &token_id, &token.extension)?;
+        self._update_royalty_token_count(
+            deps.storage,
+            had_royalty,
+            token.extension.has_payable_royalty(),
+        )?;
 
         Ok(Response::new()
-            .add_attribute("action", "mint")
-            .add_attribute("minter", info.sender.clone())
-            .add_attribute("owner", owner)
-            .add_attribute("token_id", token_id.to_string()))
+            .add_attribute("action", "update_nft_info")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
     }
 
-    pub fn update_ownership(
+    pub fn freeze_metadata(
+        &self,
         deps: DepsMut,
-        env: Env,
         info: MessageInfo,
-        action: cw_ownable::Action,
+        token_id: Option<String>,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
-        let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
-        Ok(Response::new().add_attributes(ownership.into_attributes()))
+        self.assert_minter_or_owner(deps.storage, &info.sender)?;
+        if self.is_metadata_frozen(deps.storage, token_id.as_deref())? {
+            return Err(ContractError::MetadataFrozen {});
+        }
+
+        let res = Response::new().add_attribute("action", "freeze_metadata");
+        match token_id {
+            Some(token_id) => {
+                // only existing tokens can be frozen
+                let mut token = self.tokens.load(deps.storage, &token_id)?;
+                // a reveal no longer changes the uri the token reports now
+                if self.reveal.may_load(deps.storage)?.is_some() {
+                    token.token_uri =
+                        self.resolve_token_uri(deps.storage, &token_id, token.token_uri)?;
+                    self.tokens.save(deps.storage, &token_id, &token)?;
+                }
+                self.pinned_token_uris
+                    .save(deps.storage, &token_id, &true)?;
+                self.frozen_token_metadata
+                    .save(deps.storage, &token_id, &true)?;
+                Ok(res.add_attribute("token_id", token_id))
+            }
+            None => {
+                self.metadata_frozen.save(deps.storage, &true)?;
+                Ok(res)
+            }
+        }
     }
 
-    pub fn update_minter(
+    pub fn set_default_royalty(
         &self,
         deps: DepsMut,
-        _env: Env,
         info: MessageInfo,
-        minter: String,
+        payment_address: String,
+        share: Decimal,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        if share > Decimal::from_ratio(self.max_royalty(deps.storage)?, MAX_ROYALTY_BPS) {
+            return Err(ContractError::InvalidRoyaltyPercentage);
+        }
 
-        self.minter
-            .save(deps.storage, &deps.api.addr_validate(&minter)?)?;
+        let royalty = RoyaltyInfo {
+            payment_address: deps.api.addr_validate(&payment_address)?,
+            share,
+        };
+        self.default_royalty.save(deps.storage, &royalty)?;
 
-        Ok(Response::new())
+        Ok(Response::new()
+            .add_attribute("action", "set_default_royalty")
+            .add_attribute("payment_address", royalty.payment_address)
+            .add_attribute("share", share.to_string()))
     }
 
-    pub fn withdraw_rewards(&self) -> Result<Response<ArchwayMsg>, ContractError> {
-        let msg = ArchwayMsg::withdraw_rewards_by_limit(0);
+    pub fn remove_default_royalty(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        self.default_royalty.remove(deps.storage);
 
-        let res = Response::new()
-            .add_submessage(SubMsg::reply_on_success(msg, REWARDS_WITHDRAW_REPLY))
-            .add_attribute("method", "withdraw_rewards");
+        Ok(Response::new().add_attribute("action", "remove_default_royalty"))
+    }
 
-        Ok(res)
+    pub fn set_royalty_mode(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        holders: bool,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        self.holder_royalties.save(deps.storage, &holders)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_royalty_mode")
+            .add_attribute("holders", holders.to_string()))
     }
 
-    pub fn withdraw_token_rewards(
+    pub fn receive_royalty(
         &self,
         deps: DepsMut,
-        _env: Env,
         info: MessageInfo,
-        token_id: String,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
-        let mut token = self.tokens.load(deps.storage, &token_id)?;
-        // ensure we have permissions
-        if token.owner != info.sender {
-            return Err(ContractError::Ownership(OwnershipError::NotOwner));
-        }
+        let rewards_denom = self.rewards_denom.load(deps.storage)?;
+        let amount = must_pay(&info, &rewards_denom)?;
+        // shared by all tokens exactly like withdrawn Archway rewards
+        let total = self.add_total_arch_reward(deps.storage, amount.u128())?;
 
-        let available_to_claim = self
-            .get_total_arch_rewards(deps.as_ref(), Some(token_id.clone()))
-            .unwrap()
-            .total_arch_reward;
+        Ok(Response::new()
+            .add_attribute("action", "receive_royalty")
+            .add_attribute("sender", info.sender)
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("total_arch_reward", total.to_string()))
+    }
 
-        token.reward_claimed += available_to_claim;
-        self.tokens.save(deps.storage, &token_id, &token)?;
+    pub fn sweep_rewards(
+        &self,
+        deps: DepsMut,
+        env: Env,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let swept = self._sweep_rewards(deps, &env)?;
 
-        // transfer arch as msg
+        Ok(Response::new()
+            .add_attribute("action", "sweep_rewards")
+            .add_attribute("amount", swept.to_string()))
+    }
 
-        let rewards_denom = self.rewards_denom.load(deps.storage)?;
-        let msg = BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: coins(available_to_claim, rewards_denom),
-        };
+    pub fn update_max_royalty(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        max_royalty_bps: u64,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        if max_royalty_bps > MAX_ROYALTY_BPS {
+            return Err(ContractError::InvalidRoyaltyPercentage);
+        }
+        self.max_royalty_bps.save(deps.storage, &max_royalty_bps)?;
 
         Ok(Response::new()
-            .add_message(msg)
-            .add_attribute("action", "withdraw_token_rewards")
-            .add_attribute("token_id", token_id)
-            .add_attribute("owner", info.sender)
-            .add_attribute("amount", available_to_claim.to_string()))
+            .add_attribute("action", "update_max_royalty")
+            .add_attribute("max_royalty_bps", max_royalty_bps.to_string()))
     }
-}
 
-impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
-where
-    T: Serialize + DeserializeOwned + Clone,
-    C: CustomMsg,
-    E: CustomMsg,
-    Q: CustomMsg,
-{
-    fn transfer_nft(
+    pub fn set_reveal_config(
         &self,
         deps: DepsMut,
-        env: Env,
         info: MessageInfo,
-        recipient: String,
-        token_id: String,
+        placeholder_uri: String,
+        provenance_hash: HexBinary,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
-        self._transfer_nft(deps, &env, &info, &recipient, &token_id)?;
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        // the provenance commitment can't be replaced once made
+        if self.reveal.may_load(deps.storage)?.is_some() {
+            return Err(ContractError::RevealAlreadyConfigured {});
+        }
+        // a reveal would change the uris of a frozen collection
+        if self.is_metadata_frozen(deps.storage, None)?
+            || self
+                .base_token_uri_frozen
+                .may_load(deps.storage)?
+                .unwrap_or_default()
+        {
+            return Err(ContractError::MetadataFrozen {});
+        }
+        if provenance_hash.len() != 32 {
+            return Err(ContractError::InvalidProvenanceHash {});
+        }
+
+        self.reveal.save(
+            deps.storage,
+            &RevealConfig {
+                placeholder_uri: placeholder_uri.clone(),
+                provenance_hash: provenance_hash.clone(),
+                revealed_base_uri: None,
+            },
+        )?;
 
         Ok(Response::new()
-            .add_attribute("action", "transfer_nft")
-            .add_attribute("sender", info.sender)
-            .add_attribute("recipient", recipient)
-            .add_attribute("token_id", token_id))
+            .add_attribute("action", "set_reveal_config")
+            .add_attribute("placeholder_uri", placeholder_uri)
+            .add_attribute("provenance_hash", provenance_hash.to_hex()))
     }
 
-    fn send_nft(
+    pub fn reveal(
         &self,
         deps: DepsMut,
-        env: Env,
         info: MessageInfo,
-        contract: String,
-        token_id: String,
-        msg: Binary,
+        base_uri: String,
+        provenance_preimage: String,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
-        // Transfer token
-        self._transfer_nft(deps, &env, &info, &contract, &token_id)?;
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        let mut reveal = self
+            .reveal
+            .may_load(deps.storage)?
+            .ok_or(ContractError::RevealNotConfigured {})?;
+        if reveal.revealed_base_uri.is_some() {
+            return Err(ContractError::AlreadyRevealed {});
+        }
+        if !reveal.matches(&base_uri, &provenance_preimage) {
+            return Err(ContractError::InvalidReveal {});
+        }
 
-        let send = Cw721ReceiveMsg {
-            sender: info.sender.to_string(),
-            token_id: token_id.clone(),
-            msg,
-        };
+        reveal.revealed_base_uri = Some(base_uri.clone());
+        self.reveal.save(deps.storage, &reveal)?;
 
-        // Send message
         Ok(Response::new()
-            .add_message(send.into_cosmos_msg(contract.clone())?)
-            .add_attribute("action", "send_nft")
-            .add_attribute("sender", info.sender)
-            .add_attribute("recipient", contract)
-            .add_attribute("token_id", token_id))
+            .add_attribute("action", "reveal")
+            .add_attribute("base_uri", base_uri))
     }
 
-    fn approve(
+    pub fn update_base_token_uri(
         &self,
         deps: DepsMut,
-        env: Env,
         info: MessageInfo,
-        spender: String,
-        token_id: String,
-        expires: Option<Expiration>,
+        base_token_uri: String,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
-        self._update_approvals(deps, &env, &info, &spender, &token_id, true, expires)?;
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        self.assert_base_token_uri_not_frozen(deps.storage)?;
+
+        self.base_token_uri.save(deps.storage, &base_token_uri)?;
 
         Ok(Response::new()
-            .add_attribute("action", "approve")
-            .add_attribute("sender", info.sender)
-            .add_attribute("spender", spender)
-            .add_attribute("token_id", token_id))
+            .add_attribute("action", "update_base_token_uri")
+            .add_attribute("base_token_uri", base_token_uri))
     }
 
-    fn revoke(
+    pub fn set_pause(
         &self,
         deps: DepsMut,
-        env: Env,
         info: MessageInfo,
-        spender: String,
-        token_id: String,
+        pause: PauseConfig,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
-        self._update_approvals(deps, &env, &info, &spender, &token_id, false, None)?;
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        self.pause.save(deps.storage, &pause)?;
 
         Ok(Response::new()
-            .add_attribute("action", "revoke")
-            .add_attribute("sender", info.sender)
-            .add_attribute("spender", spender)
-            .add_attribute("token_id", token_id))
+            .add_attribute("action", "set_pause")
+            .add_attribute("minting", pause.minting.to_string())
+            .add_attribute("transfers", pause.transfers.to_string())
+            .add_attribute("claims", pause.claims.to_string())
+            .add_attribute("withdrawals", pause.withdrawals.to_string()))
     }
 
-    fn approve_all(
+    pub fn block_addresses(
         &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        operator: String,
-        expires: Option<Expiration>,
+        addresses: Vec<String>,
+        reason: String,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
-        // reject expired data as invalid
-        let expires = expires.unwrap_or_default();
-        if expires.is_expired(&env.block) {
-            return Err(ContractError::Expired {});
-        }
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        // set the operator for us
-        let operator_addr = deps.api.addr_validate(&operator)?;
-        self.operators
-            .save(deps.storage, (&info.sender, &operator_addr), &expires)?;
+        let block = BlockReason {
+            reason,
+            blocked_at: env.block.time,
+        };
+        let mut events = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let addr = deps.api.addr_validate(&address)?;
+            self.blocklist.save(deps.storage, &addr, &block)?;
+            events.push(
+                Event::new("block_address")
+                    .add_attribute("address", addr)
+                    .add_attribute("reason", &block.reason)
+                    .add_attribute("owner", &info.sender),
+            );
+        }
 
         Ok(Response::new()
-            .add_attribute("action", "approve_all")
-            .add_attribute("sender", info.sender)
-            .add_attribute("operator", operator))
+            .add_attribute("action", "block_addresses")
+            .add_events(events))
     }
 
-    fn revoke_all(
+    pub fn unblock_addresses(
         &self,
         deps: DepsMut,
-        _env: Env,
         info: MessageInfo,
-        operator: String,
+        addresses: Vec<String>,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
-        let operator_addr = deps.api.addr_validate(&operator)?;
-        self.operators
-            .remove(deps.storage, (&info.sender, &operator_addr));
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let mut events = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let addr = deps.api.addr_validate(&address)?;
+            self.blocklist.remove(deps.storage, &addr);
+            events.push(
+                Event::new("unblock_address")
+                    .add_attribute("address", addr)
+                    .add_attribute("owner", &info.sender),
+            );
+        }
 
         Ok(Response::new()
-            .add_attribute("action", "revoke_all")
-            .add_attribute("sender", info.sender)
-            .add_attribute("operator", operator))
+            .add_attribute("action", "unblock_addresses")
+            .add_events(events))
     }
 
-    fn burn(
+    pub fn set_token_lock(
         &self,
-        _deps: DepsMut,
-        _env: Env,
-        _info: MessageInfo,
-        _token_id: String,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        locked: bool,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
-        Err(ContractError::BurnNotAllowed {})
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        // only existing tokens can be locked
+        self.tokens.load(deps.storage, &token_id)?;
+
+        if locked {
+            self.locked_tokens.save(deps.storage, &token_id, &true)?;
+        } else {
+            self.locked_tokens.remove(deps.storage, &token_id);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_token_lock")
+            .add_attribute("token_id", token_id)
+            .add_attribute("locked", locked.to_string()))
     }
-}
+
+    pub fn freeze_base_token_uri(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        self.assert_base_token_uri_not_frozen(deps.storage)?;
+
+        self.base_token_uri_frozen.save(deps.storage, &true)?;
+
+        Ok(Response::new().add_attribute("action", "freeze_base_token_uri"))
+    }
+
+    pub fn public_mint(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_uri: Option<String>,
+        extension: T,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self.assert_not_paused(deps.storage, "minting", |p| p.minting)?;
+        let config = self
+            .mint_config
+            .may_load(deps.storage)?
+            .ok_or(ContractError::PublicMintDisabled {})?;
+
+        if let Some(start_time) = config.start_time {
+            if env.block.time < start_time {
+                return Err(ContractError::MintNotStarted {});
+            }
+        }
+        if let Some(end_time) = config.end_time {
+            if env.block.time >= end_time {
+                return Err(ContractError::MintEnded {});
+            }
+        }
+
+        let minted = self
+            .address_mint_count
+            .may_load(deps.storage, &info.sender)?
+            .unwrap_or_default();
+        if let Some(limit) = config.per_address_limit {
+            if minted >= limit {
+                return Err(ContractError::MintLimitReached { limit });
+            }
+        }
+
+        self._check_payment(&info, &config.price)?;
+
+        self.assert_supply(deps.storage, 1)?;
+        let token_id = self.next_token_id(deps.storage)?;
+        self._mint_token(
+            deps.storage,
+            &token_id,
+            info.sender.clone(),
+            token_uri,
+            extension,
+        )?;
+        self.address_mint_count
+            .save(deps.storage, &info.sender, &(minted + 1))?;
+
+        let mut res = Response::new()
+            .add_attribute("action", "public_mint")
+            .add_attribute("minter", info.sender.clone())
+            .add_attribute("owner", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("price", config.price.to_string());
+
+        if let Some(msg) = self._route_proceeds(deps.storage, config.price, config.treasury)? {
+            res = res.add_message(msg);
+        }
+
+        Ok(res)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn whitelist_mint(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        allocation: u32,
+        proof: Vec<HexBinary>,
+        token_uri: Option<String>,
+        extension: T,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self.assert_not_paused(deps.storage, "minting", |p| p.minting)?;
+        let (phase_id, phase) = self
+            .active_mint_phase(deps.storage, &env.block)?
+            .ok_or(ContractError::NoActiveMintPhase {})?;
+
+        if !phase.verify(&info.sender, allocation, &proof) {
+            return Err(ContractError::InvalidMerkleProof {});
+        }
+
+        let minted = self
+            .phase_mint_count
+            .may_load(deps.storage, (phase_id, &info.sender))?
+            .unwrap_or_default();
+        if minted >= allocation {
+            return Err(ContractError::MintLimitReached { limit: allocation });
+        }
+
+        self._check_payment(&info, &phase.price)?;
+
+        self.assert_supply(deps.storage, 1)?;
+        let token_id = self.next_token_id(deps.storage)?;
+        self._mint_token(
+            deps.storage,
+            &token_id,
+            info.sender.clone(),
+            token_uri,
+            extension,
+        )?;
+        self.phase_mint_count
+            .save(deps.storage, (phase_id, &info.sender), &(minted + 1))?;
+
+        let mut res = Response::new()
+            .add_attribute("action", "whitelist_mint")
+            .add_attribute("phase_id", phase_id.to_string())
+            .add_attribute("minter", info.sender.clone())
+            .add_attribute("owner", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("price", phase.price.to_string());
+
+        if let Some(msg) = self._route_proceeds(deps.storage, phase.price, phase.treasury)? {
+            res = res.add_message(msg);
+        }
+
+        Ok(res)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_mint_phase(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        phase_id: u32,
+        merkle_root: HexBinary,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        price: Coin,
+        treasury: Option<String>,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        if end_time <= start_time {
+            return Err(ContractError::InvalidMintConfig {
+                reason: "end_time must be after start_time".to_string(),
+            });
+        }
+        if merkle_root.len() != 32 {
+            return Err(ContractError::InvalidMintConfig {
+                reason: "merkle_root must be a sha256 hash".to_string(),
+            });
+        }
+        let treasury = treasury
+            .map(|treasury| deps.api.addr_validate(&treasury))
+            .transpose()?;
+        self._validate_sale_price(deps.storage, &price, &treasury)?;
+
+        self.mint_phases.save(
+            deps.storage,
+            phase_id,
+            &MintPhase {
+                merkle_root,
+                start_time,
+                end_time,
+                price,
+                treasury,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_mint_phase")
+            .add_attribute("phase_id", phase_id.to_string()))
+    }
+
+    pub fn remove_mint_phase(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        phase_id: u32,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        self.mint_phases.remove(deps.storage, phase_id);
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_mint_phase")
+            .add_attribute("phase_id", phase_id.to_string()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_mint_config(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        price: Coin,
+        per_address_limit: Option<u32>,
+        start_time: Option<Timestamp>,
+        end_time: Option<Timestamp>,
+        treasury: Option<String>,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        if let (Some(start_time), Some(end_time)) = (start_time, end_time) {
+            if end_time <= start_time {
+                return Err(ContractError::InvalidMintConfig {
+                    reason: "end_time must be after start_time".to_string(),
+                });
+            }
+        }
+
+        let treasury = treasury
+            .map(|treasury| deps.api.addr_validate(&treasury))
+            .transpose()?;
+        self._validate_sale_price(deps.storage, &price, &treasury)?;
+
+        self.mint_config.save(
+            deps.storage,
+            &MintConfig {
+                price,
+                per_address_limit,
+                start_time,
+                end_time,
+                treasury,
+            },
+        )?;
+
+        Ok(Response::new().add_attribute("action", "set_mint_config"))
+    }
+
+    pub fn update_ownership(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        action: cw_ownable::Action,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
+        Ok(Response::new().add_attributes(ownership.into_attributes()))
+    }
+
+    pub fn update_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        minter: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        self.minter
+            .save(deps.storage, &deps.api.addr_validate(&minter)?)?;
+
+        Ok(Response::new())
+    }
+
+    pub fn update_withdraw_config(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        min_interval: Option<Duration>,
+        min_pending_rewards: Option<Uint128>,
+        keepers: Option<Vec<String>>,
+        keeper_bounty: Option<KeeperBounty>,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        if let Some(bounty) = &keeper_bounty {
+            if bounty.bps > 10_000 {
+                return Err(ContractError::InvalidKeeperBounty {});
+            }
+        }
+
+        let keepers = keepers
+            .map(|keepers| {
+                keepers
+                    .iter()
+                    .map(|keeper| deps.api.addr_validate(keeper))
+                    .collect::<StdResult<Vec<_>>>()
+            })
+            .transpose()?;
+
+        // unset fields keep their value, empty ones lift the restriction
+        let mut config = self
+            .withdraw_config
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        if let Some(min_interval) = min_interval {
+            config.min_interval = match min_interval {
+                Duration::Height(0) | Duration::Time(0) => None,
+                interval => Some(interval),
+            };
+        }
+        if let Some(min_pending_rewards) = min_pending_rewards {
+            config.min_pending_rewards = Some(min_pending_rewards).filter(|min| !min.is_zero());
+        }
+        if let Some(keepers) = keepers {
+            config.keepers = Some(keepers).filter(|keepers| !keepers.is_empty());
+        }
+        if let Some(keeper_bounty) = keeper_bounty {
+            config.keeper_bounty = Some(keeper_bounty).filter(|bounty| bounty.bps > 0);
+        }
+        self.withdraw_config.save(deps.storage, &config)?;
+
+        Ok(Response::new().add_attribute("action", "update_withdraw_config"))
+    }
+
+    pub fn withdraw_rewards(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self.assert_not_paused(deps.storage, "withdrawals", |p| p.withdrawals)?;
+        let config = self
+            .withdraw_config
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+
+        if let Some(keepers) = &config.keepers {
+            if !keepers.contains(&info.sender) {
+                return Err(ContractError::NotKeeper {});
+            }
+        }
+
+        if let (Some(interval), Some(last)) = (
+            config.min_interval,
+            self.last_rewards_withdraw.may_load(deps.storage)?,
+        ) {
+            let next = interval.after(&last);
+            if !next.is_expired(&env.block) {
+                return Err(ContractError::WithdrawTooSoon { next });
+            }
+        }
+
+        if let Some(threshold) = config.min_pending_rewards {
+            let pending = self.pending_rewards(deps.as_ref(), &env)?;
+            if pending < threshold {
+                return Err(ContractError::BelowWithdrawThreshold { pending, threshold });
+            }
+        }
+
+        // royalties sent without `ReceiveRoyalty` are shared along with the withdrawn rewards
+        self._sweep_rewards(deps.branch(), &env)?;
+        self.last_rewards_withdraw.save(deps.storage, &env.block)?;
+        // the reply handler pays the bounty to whoever triggered the withdrawal
+        self.rewards_withdraw_caller
+            .save(deps.storage, &info.sender)?;
+
+        let msg = ArchwayMsg::withdraw_rewards_by_limit(0);
+
+        let res = Response::new()
+            .add_submessage(SubMsg::reply_on_success(msg, REWARDS_WITHDRAW_REPLY))
+            .add_attribute("method", "withdraw_rewards");
+
+        Ok(res)
+    }
+
+    pub fn withdraw_token_rewards(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self.assert_not_paused(deps.storage, "claims", |p| p.claims)?;
+        self.assert_not_blocked(deps.storage, &info.sender)?;
+        let (token, available_to_claim) =
+            self._pending_token_rewards(deps.storage, &info.sender, &token_id)?;
+        if available_to_claim.is_zero() {
+            return Err(ContractError::NothingToClaim {});
+        }
+
+        let rewards_denom = self._assert_reward_balance(deps.as_ref(), &env, available_to_claim)?;
+        self._record_token_claim(deps.storage, &token_id, token, available_to_claim)?;
+
+        // transfer arch as msg
+        let msg = BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(available_to_claim.u128(), rewards_denom),
+        };
+
+        Ok(Response::new()
+            .add_message(msg)
+            .add_attribute("action", "withdraw_token_rewards")
+            .add_attribute("token_id", token_id)
+            .add_attribute("owner", info.sender)
+            .add_attribute("amount", available_to_claim.to_string()))
+    }
+
+    pub fn batch_withdraw_token_rewards(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        mut token_ids: Vec<String>,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self.assert_not_paused(deps.storage, "claims", |p| p.claims)?;
+        self.assert_not_blocked(deps.storage, &info.sender)?;
+        // a token listed twice must not be paid twice
+        token_ids.sort();
+        token_ids.dedup();
+
+        let mut claims = vec![];
+        let mut total = Uint128::zero();
+        for token_id in token_ids {
+            let (token, amount) =
+                self._pending_token_rewards(deps.storage, &info.sender, &token_id)?;
+            // tokens with nothing to claim are skipped rather than failing the batch
+            if amount.is_zero() {
+                continue;
+            }
+            total = total
+                .checked_add(amount)
+                .map_err(|_| ContractError::RewardOverflow {})?;
+            claims.push((token_id, token, amount));
+        }
+        if total.is_zero() {
+            return Err(ContractError::NothingToClaim {});
+        }
+
+        let rewards_denom = self._assert_reward_balance(deps.as_ref(), &env, total)?;
+        let mut claimed_ids = vec![];
+        for (token_id, token, amount) in claims {
+            self._record_token_claim(deps.storage, &token_id, token, amount)?;
+            claimed_ids.push(token_id);
+        }
+
+        let msg = BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(total.u128(), rewards_denom),
+        };
+
+        Ok(Response::new()
+            .add_message(msg)
+            .add_attribute("action", "batch_withdraw_token_rewards")
+            .add_attribute("token_ids", claimed_ids.join(","))
+            .add_attribute("owner", info.sender)
+            .add_attribute("amount", total.to_string()))
+    }
+}
+
+impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
+where
+    T: Serialize + DeserializeOwned + Clone + RoyaltyExtension,
+    C: CustomMsg,
+    E: CustomMsg,
+    Q: CustomMsg,
+{
+    fn transfer_nft(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: String,
+        token_id: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self._transfer_nft(deps, &env, &info, &recipient, &token_id)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", recipient)
+            .add_attribute("token_id", token_id))
+    }
+
+    fn send_nft(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        // Transfer token
+        self._transfer_nft(deps, &env, &info, &contract, &token_id)?;
+
+        let send = Cw721ReceiveMsg {
+            sender: info.sender.to_string(),
+            token_id: token_id.clone(),
+            msg,
+        };
+
+        // Send message
+        Ok(Response::new()
+            .add_message(send.into_cosmos_msg(contract.clone())?)
+            .add_attribute("action", "send_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", contract)
+            .add_attribute("token_id", token_id))
+    }
+
+    fn batch_transfer_nft(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: String,
+        token_ids: Vec<String>,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        Self::assert_unique_token_ids(&token_ids)?;
+
+        // any failing token reverts the whole batch
+        for token_id in &token_ids {
+            self._transfer_nft(deps.branch(), &env, &info, &recipient, token_id)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "batch_transfer_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", recipient)
+            .add_attribute("token_ids", token_ids.join(",")))
+    }
+
+    fn batch_send_nft(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract: String,
+        token_ids: Vec<String>,
+        msg: Binary,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        Self::assert_unique_token_ids(&token_ids)?;
+
+        // any failing token reverts the whole batch
+        let mut messages = Vec::with_capacity(token_ids.len());
+        for token_id in &token_ids {
+            self._transfer_nft(deps.branch(), &env, &info, &contract, token_id)?;
+            let send = Cw721ReceiveMsg {
+                sender: info.sender.to_string(),
+                token_id: token_id.clone(),
+                msg: msg.clone(),
+            };
+            messages.push(send.into_cosmos_msg(contract.clone())?);
+        }
+
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "batch_send_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", contract)
+            .add_attribute("token_ids", token_ids.join(",")))
+    }
+
+    fn list_nft(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        price: Uint128,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        if token.owner != info.sender {
+            return Err(ContractError::Ownership(OwnershipError::NotOwner));
+        }
+        // fail early rather than at purchase
+        self.assert_not_blocked(deps.storage, &info.sender)?;
+        self.assert_transferable(deps.storage, Some(&token_id))?;
+        if price.is_zero() {
+            return Err(ContractError::InvalidListing {
+                reason: "price must not be zero".to_string(),
+            });
+        }
+
+        let price = Coin {
+            denom: self.rewards_denom.load(deps.storage)?,
+            amount: price,
+        };
+        self.listings.save(
+            deps.storage,
+            &token_id,
+            &Listing {
+                seller: info.sender.clone(),
+                price: price.clone(),
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "list_nft")
+            .add_attribute("seller", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("price", price.to_string()))
+    }
+
+    fn cancel_listing(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let listing = self.listings.load(deps.storage, &token_id)?;
+        let owner = self.tokens.load(deps.storage, &token_id)?.owner;
+        if info.sender != listing.seller && info.sender != owner {
+            return Err(ContractError::Ownership(OwnershipError::NotOwner));
+        }
+        self.listings.remove(deps.storage, &token_id)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_listing")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    fn buy_nft(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let listing = self.listings.load(deps.storage, &token_id)?;
+        self._check_payment(&info, &listing.price)?;
+        if info.sender == listing.seller {
+            return Err(ContractError::InvalidListing {
+                reason: "seller can't buy their own token".to_string(),
+            });
+        }
+        // listings are not escrowed, the seller must still own the token
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        if token.owner != listing.seller {
+            return Err(ContractError::InvalidListing {
+                reason: "seller no longer owns the token".to_string(),
+            });
+        }
+
+        // the seller keeps the rewards accrued while they held the token
+        let (token, settled) =
+            self._settle_rewards(deps.branch(), &env, &token_id, token, listing.price.amount)?;
+        let res = self._complete_sale(
+            deps,
+            &env,
+            &token_id,
+            token,
+            listing.seller,
+            info.sender.clone(),
+            &listing.price,
+            settled,
+        )?;
+
+        Ok(res
+            .add_attribute("action", "buy_nft")
+            .add_attribute("buyer", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("price", listing.price.to_string()))
+    }
+
+    fn make_offer(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        expires: Option<Expiration>,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let rewards_denom = self.rewards_denom.load(deps.storage)?;
+        let amount = must_pay(&info, &rewards_denom)?;
+        // reject expired data as invalid
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        if token.owner == info.sender {
+            return Err(ContractError::InvalidOffer {
+                reason: "owner can't bid on their own token".to_string(),
+            });
+        }
+        self.assert_not_blocked(deps.storage, &info.sender)?;
+        self.assert_transferable(deps.storage, Some(&token_id))?;
+
+        // a new offer replaces the bidder's previous one
+        let previous = self
+            .offers
+            .may_load(deps.storage, (&token_id, &info.sender))?;
+        self.offers.save(
+            deps.storage,
+            (&token_id, &info.sender),
+            &Offer {
+                amount: coin(amount.u128(), &rewards_denom),
+                expires,
+            },
+        )?;
+
+        let released = previous
+            .as_ref()
+            .map(|offer| offer.amount.amount)
+            .unwrap_or_default();
+        self._update_escrow(deps.storage, amount, released)?;
+
+        let mut res = Response::new();
+        if let Some(previous) = previous {
+            res = res.add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![previous.amount],
+            });
+        }
+        Ok(res
+            .add_attribute("action", "make_offer")
+            .add_attribute("bidder", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("amount", amount)
+            .add_attribute("expires", expires.to_string()))
+    }
+
+    fn accept_offer(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        bidder: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        // ensure we have permissions
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+
+        let bidder = deps.api.addr_validate(&bidder)?;
+        let offer = self.offers.load(deps.storage, (&token_id, &bidder))?;
+        if offer.expires.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+        self.offers.remove(deps.storage, (&token_id, &bidder));
+        // the offer pays for the sale, so it is no longer held for the bidder
+        self._update_escrow(deps.storage, Uint128::zero(), offer.amount.amount)?;
+
+        let seller = token.owner.clone();
+        // the seller keeps the rewards accrued while they held the token
+        let (token, settled) =
+            self._settle_rewards(deps.branch(), &env, &token_id, token, offer.amount.amount)?;
+        let res = self._complete_sale(
+            deps,
+            &env,
+            &token_id,
+            token,
+            seller,
+            bidder.clone(),
+            &offer.amount,
+            settled,
+        )?;
+
+        Ok(res
+            .add_attribute("action", "accept_offer")
+            .add_attribute("sender", info.sender)
+            .add_attribute("bidder", bidder)
+            .add_attribute("token_id", token_id)
+            .add_attribute("price", offer.amount.to_string()))
+    }
+
+    fn cancel_offer(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let offer = self.offers.load(deps.storage, (&token_id, &info.sender))?;
+        self.offers.remove(deps.storage, (&token_id, &info.sender));
+        self._update_escrow(deps.storage, Uint128::zero(), offer.amount.amount)?;
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![offer.amount.clone()],
+            })
+            .add_attribute("action", "cancel_offer")
+            .add_attribute("bidder", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("amount", offer.amount.to_string()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn start_auction(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        reserve: Uint128,
+        duration: Duration,
+        min_increment: Uint128,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        if reserve.is_zero() {
+            return Err(ContractError::InvalidAuction {
+                reason: "reserve must not be zero".to_string(),
+            });
+        }
+        if min_increment.is_zero() {
+            return Err(ContractError::InvalidAuction {
+                reason: "min increment must not be zero".to_string(),
+            });
+        }
+        if matches!(duration, Duration::Height(0) | Duration::Time(0)) {
+            return Err(ContractError::InvalidAuction {
+                reason: "duration must not be zero".to_string(),
+            });
+        }
+
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        // ensure we have permissions
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        self.assert_can_move(
+            deps.storage,
+            &info.sender,
+            &token_id,
+            &token,
+            &env.contract.address,
+        )?;
+        let seller = token.owner.clone();
+        // the seller keeps the rewards accrued while they held the token, those accrued in
+        // escrow go with it
+        let (token, settled) =
+            self._settle_rewards(deps.branch(), &env, &token_id, token, Uint128::zero())?;
+        // escrow the token so it can't move while bids are open, it still counts for the seller
+        self._set_owner(deps.storage, &token_id, token, env.contract.address.clone())?;
+
+        let reserve = Coin {
+            denom: self.rewards_denom.load(deps.storage)?,
+            amount: reserve,
+        };
+        let end = duration.after(&env.block);
+        self.auctions.save(
+            deps.storage,
+            &token_id,
+            &Auction {
+                seller: seller.clone(),
+                reserve: reserve.clone(),
+                min_increment,
+                end,
+                highest_bid: None,
+            },
+        )?;
+
+        let mut res = Response::new();
+        if !settled.is_zero() {
+            res = res.add_message(BankMsg::Send {
+                to_address: seller.to_string(),
+                amount: coins(settled.u128(), &reserve.denom),
+            });
+        }
+        Ok(res
+            .add_attribute("action", "start_auction")
+            .add_attribute("seller", seller)
+            .add_attribute("token_id", token_id)
+            .add_attribute("reserve", reserve.to_string())
+            .add_attribute("end", end.to_string())
+            .add_attribute("settled_rewards", settled))
+    }
+
+    fn bid(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let mut auction = self.auctions.load(deps.storage, &token_id)?;
+        let amount = must_pay(&info, &auction.reserve.denom)?;
+        if auction.end.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+        if info.sender == auction.seller {
+            return Err(ContractError::InvalidAuction {
+                reason: "seller can't bid on their own token".to_string(),
+            });
+        }
+        self.assert_not_blocked(deps.storage, &info.sender)?;
+
+        let minimum = match &auction.highest_bid {
+            Some(highest) => highest.amount + auction.min_increment,
+            None => auction.reserve.amount,
+        };
+        if amount < minimum {
+            return Err(ContractError::InvalidAuction {
+                reason: format!("bid must be at least {minimum}"),
+            });
+        }
+
+        let previous = auction.highest_bid.replace(AuctionBid {
+            bidder: info.sender.clone(),
+            amount,
+        });
+        self.auctions.save(deps.storage, &token_id, &auction)?;
+        let released = previous.as_ref().map(|bid| bid.amount).unwrap_or_default();
+        self._update_escrow(deps.storage, amount, released)?;
+
+        let mut res = Response::new();
+        if let Some(previous) = previous {
+            res = res.add_message(BankMsg::Send {
+                to_address: previous.bidder.to_string(),
+                amount: coins(previous.amount.u128(), &auction.reserve.denom),
+            });
+        }
+        Ok(res
+            .add_attribute("action", "bid")
+            .add_attribute("bidder", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("amount", amount))
+    }
+
+    fn settle_auction(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        token_id: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let auction = self.auctions.load(deps.storage, &token_id)?;
+        if !auction.end.is_expired(&env.block) {
+            return Err(ContractError::InvalidAuction {
+                reason: "auction has not ended".to_string(),
+            });
+        }
+
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        let can_move = match &auction.highest_bid {
+            Some(highest) => match self.assert_can_move(
+                deps.storage,
+                &auction.seller,
+                &token_id,
+                &token,
+                &highest.bidder,
+            ) {
+                Ok(()) => true,
+                // the token can't reach the winner while the lock or the block lasts
+                Err(ContractError::Blocked { .. } | ContractError::NonTransferable {}) => false,
+                // anything else, such as a pause, can be waited out and settled again
+                Err(err) => return Err(err),
+            },
+            None => false,
+        };
+        self.auctions.remove(deps.storage, &token_id);
+
+        let res = match auction.highest_bid {
+            Some(highest) if can_move => {
+                // the winning bid pays for the sale, so it is no longer held for the bidder
+                self._update_escrow(deps.storage, Uint128::zero(), highest.amount)?;
+                let price = coin(highest.amount.u128(), &auction.reserve.denom);
+                // the seller's rewards were settled when the auction started
+                self._complete_sale(
+                    deps,
+                    &env,
+                    &token_id,
+                    token,
+                    auction.seller.clone(),
+                    highest.bidder.clone(),
+                    &price,
+                    Uint128::zero(),
+                )?
+                .add_attribute("winner", highest.bidder)
+                .add_attribute("price", price.to_string())
+            }
+            highest => {
+                // nobody bid, or the token can't reach the winner: the contract hands it back
+                // and refunds the winning bid
+                self._return_escrowed(deps.storage, &token_id, token, auction.seller.clone())?;
+                let mut res = Response::new().add_attribute("seller", auction.seller);
+                if let Some(highest) = highest {
+                    self._update_escrow(deps.storage, Uint128::zero(), highest.amount)?;
+                    res = res
+                        .add_message(BankMsg::Send {
+                            to_address: highest.bidder.to_string(),
+                            amount: coins(highest.amount.u128(), &auction.reserve.denom),
+                        })
+                        .add_attribute("refunded", highest.bidder);
+                }
+                res
+            }
+        };
+
+        Ok(res
+            .add_attribute("action", "settle_auction")
+            .add_attribute("token_id", token_id))
+    }
+
+    fn approve(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self._update_approvals(deps, &env, &info, &spender, &token_id, true, expires)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "approve")
+            .add_attribute("sender", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("token_id", token_id))
+    }
+
+    fn revoke(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        token_id: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self._update_approvals(deps, &env, &info, &spender, &token_id, false, None)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke")
+            .add_attribute("sender", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("token_id", token_id))
+    }
+
+    fn approve_all(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+        expires: Option<Expiration>,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self.assert_transferable(deps.storage, None)?;
+        // reject expired data as invalid
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+
+        // set the operator for us
+        let operator_addr = deps.api.addr_validate(&operator)?;
+        self.operators
+            .save(deps.storage, (&info.sender, &operator_addr), &expires)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "approve_all")
+            .add_attribute("sender", info.sender)
+            .add_attribute("operator", operator))
+    }
+
+    fn revoke_all(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        operator: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let operator_addr = deps.api.addr_validate(&operator)?;
+        self.operators
+            .remove(deps.storage, (&info.sender, &operator_addr));
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke_all")
+            .add_attribute("sender", info.sender)
+            .add_attribute("operator", operator))
+    }
+
+    fn burn(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _token_id: String,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        Err(ContractError::BurnNotAllowed {})
+    }
+}
 
 // helpers
 impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + RoyaltyExtension,
     C: CustomMsg,
     E: CustomMsg,
     Q: CustomMsg,
 {
+    /// Loads a token owned by `sender` together with the rewards it can withdraw
+    pub fn _pending_token_rewards(
+        &self,
+        storage: &dyn Storage,
+        sender: &Addr,
+        token_id: &str,
+    ) -> Result<(TokenInfo<T>, Uint128), ContractError> {
+        let token = self.tokens.load(storage, token_id)?;
+        // ensure we have permissions
+        if token.owner != *sender {
+            return Err(ContractError::Ownership(OwnershipError::NotOwner));
+        }
+
+        let available = self.token_rewards(storage, token.reward_claimed)?;
+        Ok((token, available))
+    }
+
+    pub fn _record_token_claim(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        mut token: TokenInfo<T>,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        token.reward_claimed = Uint128::new(token.reward_claimed)
+            .checked_add(amount)
+            .map_err(|_| ContractError::RewardOverflow {})?
+            .u128();
+        self.tokens.save(storage, token_id, &token)?;
+        self.add_total_rewards_claimed(storage, amount.u128())?;
+        Ok(())
+    }
+
+    /// Batches move tokens in the caller's order, so each token may appear only once
+    pub fn assert_unique_token_ids(token_ids: &[String]) -> Result<(), ContractError> {
+        let mut seen = HashSet::with_capacity(token_ids.len());
+        for token_id in token_ids {
+            if !seen.insert(token_id) {
+                return Err(ContractError::DuplicateTokenId {
+                    token_id: token_id.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks for the minter minting `count` tokens, run before any token id is taken
+    pub fn assert_can_mint(
+        &self,
+        storage: &dyn Storage,
+        sender: &Addr,
+        count: u64,
+    ) -> Result<(), ContractError> {
+        self.assert_not_paused(storage, "minting", |p| p.minting)?;
+        self.assert_minter(storage, sender)?;
+        self.assert_supply(storage, count)
+    }
+
+    pub fn assert_minter(&self, storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+        let minter = self.minter.load(storage)?;
+
+        if minter != *sender {
+            return Err(ContractError::Std(StdError::generic_err("Not minter")));
+        }
+        Ok(())
+    }
+
+    /// The minter and the contract owner may both manage token metadata
+    pub fn assert_minter_or_owner(
+        &self,
+        storage: &dyn Storage,
+        sender: &Addr,
+    ) -> Result<(), ContractError> {
+        if self.minter.load(storage)? == *sender {
+            return Ok(());
+        }
+        cw_ownable::assert_owner(storage, sender)?;
+        Ok(())
+    }
+
+    /// Fails if the owner paused `scope`, `paused` picks the matching flag
+    pub fn assert_not_paused(
+        &self,
+        storage: &dyn Storage,
+        scope: &str,
+        paused: impl Fn(&PauseConfig) -> bool,
+    ) -> Result<(), ContractError> {
+        if let Some(pause) = self.pause.may_load(storage)? {
+            if paused(&pause) {
+                return Err(ContractError::Paused {
+                    scope: scope.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn assert_base_token_uri_not_frozen(
+        &self,
+        storage: &dyn Storage,
+    ) -> Result<(), ContractError> {
+        if self
+            .base_token_uri_frozen
+            .may_load(storage)?
+            .unwrap_or_default()
+        {
+            return Err(ContractError::BaseTokenUriFrozen {});
+        }
+        Ok(())
+    }
+
+    pub fn _check_payment(&self, info: &MessageInfo, price: &Coin) -> Result<(), ContractError> {
+        if price.amount.is_zero() {
+            nonpayable(info)?;
+        } else {
+            let paid = must_pay(info, &price.denom)?;
+            if paid != price.amount {
+                return Err(ContractError::IncorrectPayment {
+                    expected: price.amount,
+                    received: paid,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Proceeds kept in the reward pool must be paid out in the rewards denom
+    pub fn _validate_sale_price(
+        &self,
+        storage: &dyn Storage,
+        price: &Coin,
+        treasury: &Option<Addr>,
+    ) -> Result<(), ContractError> {
+        if treasury.is_none() && !price.amount.is_zero() {
+            let rewards_denom = self.rewards_denom.load(storage)?;
+            if price.denom != rewards_denom {
+                return Err(ContractError::InvalidMintConfig {
+                    reason: format!("price must be in {rewards_denom} without a treasury"),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Forwards mint proceeds to the treasury, or credits them to the reward pool
+    pub fn _route_proceeds(
+        &self,
+        storage: &mut dyn Storage,
+        price: Coin,
+        treasury: Option<Addr>,
+    ) -> Result<Option<BankMsg>, ContractError> {
+        if price.amount.is_zero() {
+            return Ok(None);
+        }
+        match treasury {
+            Some(treasury) => Ok(Some(BankMsg::Send {
+                to_address: treasury.into_string(),
+                amount: vec![price],
+            })),
+            // proceeds stay in the contract and are shared by all holders
+            None => {
+                self.add_total_arch_reward(storage, price.amount.u128())?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Mints a token for the minter once `assert_can_mint` passed
+    fn _mint(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+        owner: String,
+        token_uri: Option<String>,
+        extension: T,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        self._mint_token(deps.storage, &token_id, owner_addr, token_uri, extension)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "mint")
+            .add_attribute("minter", info.sender.clone())
+            .add_attribute("owner", owner)
+            .add_attribute("token_id", token_id.to_string()))
+    }
+
+    pub fn _mint_token(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        owner: Addr,
+        token_uri: Option<String>,
+        extension: T,
+    ) -> Result<(), ContractError> {
+        self.assert_not_blocked(storage, &owner)?;
+        let royalty = extension.has_payable_royalty();
+        let token = TokenInfo {
+            owner: owner.clone(),
+            approvals: vec![],
+            token_uri,
+            reward_claimed: 0,
+            extension,
+        };
+
+        let token = self.tokens.update(storage, token_id, |old| match old {
+            Some(_) => Err(ContractError::Claimed {}),
+            None => Ok(token),
+        })?;
+        self.add_token_traits(storage, token_id, &token.extension)?;
+
+        self.increment_tokens(storage)?;
+        self.increment_balance(storage, &owner)?;
+        self._update_royalty_token_count(storage, false, royalty)?;
+        Ok(())
+    }
+
+    /// Sums the contract's rewards records in `rewards_denom` that are not withdrawn yet,
+    /// over every page of records
+    pub fn pending_rewards(&self, deps: Deps, env: &Env) -> StdResult<Uint128> {
+        let rewards_denom = self.rewards_denom.load(deps.storage)?;
+        let querier = QuerierWrapper::<ArchwayQuery>::new(&*deps.querier);
+
+        let mut pending = Uint128::zero();
+        let mut next_key = None;
+        loop {
+            let mut page = PageRequest::new();
+            if let Some(key) = next_key {
+                page = page.key(key);
+            }
+            let res: RewardsRecordsResponse = querier.query(&QueryRequest::Custom(
+                ArchwayQuery::rewards_records_with_pagination(
+                    env.contract.address.to_string(),
+                    page,
+                ),
+            ))?;
+            pending = res
+                .records
+                .iter()
+                .flat_map(|record| record.rewards.iter())
+                .filter(|coin| coin.denom == rewards_denom)
+                .fold(pending, |acc, coin| acc.saturating_add(coin.amount));
+
+            next_key = res
+                .pagination
+                .and_then(|pagination| pagination.next_key)
+                .filter(|key| !key.is_empty());
+            if next_key.is_none() {
+                return Ok(pending);
+            }
+        }
+    }
+
+    /// Fails with a typed error rather than letting the bank module reject the send.
+    /// Returns the rewards denom on success.
+    pub fn _assert_reward_balance(
+        &self,
+        deps: Deps,
+        env: &Env,
+        required: Uint128,
+    ) -> Result<String, ContractError> {
+        let rewards_denom = self.rewards_denom.load(deps.storage)?;
+        // funds escrowed for offers and bids belong to the bidders
+        let escrowed = self.escrow.may_load(deps.storage)?.unwrap_or_default();
+        let balance = deps
+            .querier
+            .query_balance(&env.contract.address, &rewards_denom)?
+            .amount
+            .saturating_sub(escrowed);
+        if balance < required {
+            return Err(ContractError::InsufficientRewardBalance {
+                denom: rewards_denom,
+                available: balance,
+                required,
+            });
+        }
+        Ok(rewards_denom)
+    }
+
+    /// Credits the rewards denom balance not owed to token holders or held in escrow to the
+    /// reward pool, returning the amount credited
+    fn _sweep_rewards(&self, deps: DepsMut, env: &Env) -> Result<Uint128, ContractError> {
+        let rewards_denom = self.rewards_denom.load(deps.storage)?;
+        let escrowed = self.escrow.may_load(deps.storage)?.unwrap_or_default();
+        let owed = Uint128::new(self.outstanding_rewards(deps.storage)?) + escrowed;
+        let surplus = deps
+            .querier
+            .query_balance(&env.contract.address, &rewards_denom)?
+            .amount
+            .saturating_sub(owed);
+        if !surplus.is_zero() {
+            self.add_total_arch_reward(deps.storage, surplus.u128())?;
+        }
+        Ok(surplus)
+    }
+
+    /// Keeps `royalty_token_count` in step with a token gaining or losing a payable royalty
+    fn _update_royalty_token_count(
+        &self,
+        storage: &mut dyn Storage,
+        had_royalty: bool,
+        has_royalty: bool,
+    ) -> StdResult<()> {
+        if had_royalty == has_royalty {
+            return Ok(());
+        }
+        let count = self
+            .royalty_token_count
+            .may_load(storage)?
+            .unwrap_or_default();
+        let count = if has_royalty {
+            count + 1
+        } else {
+            count.saturating_sub(1)
+        };
+        self.royalty_token_count.save(storage, &count)
+    }
+
+    fn _update_escrow(
+        &self,
+        storage: &mut dyn Storage,
+        added: Uint128,
+        released: Uint128,
+    ) -> StdResult<()> {
+        let escrowed = self.escrow.may_load(storage)?.unwrap_or_default();
+        self.escrow
+            .save(storage, &(escrowed + added).checked_sub(released)?)
+    }
+
     pub fn _transfer_nft(
         &self,
         deps: DepsMut,
@@ -366,13 +2086,170 @@ where
         recipient: &str,
         token_id: &str,
     ) -> Result<TokenInfo<T>, ContractError> {
-        let mut token = self.tokens.load(deps.storage, token_id)?;
+        let token = self.tokens.load(deps.storage, token_id)?;
         // ensure we have permissions
         self.check_can_send(deps.as_ref(), env, info, &token)?;
+        let recipient = deps.api.addr_validate(recipient)?;
+        self._move_token(deps.storage, &info.sender, token_id, token, recipient)
+    }
+
+    /// Records the rewards `token` has accrued as claimed, to be paid to the owner it is about
+    /// to leave. `held` is the part of the balance owed to someone else in the same message,
+    /// such as a sale price.
+    pub fn _settle_rewards(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        token_id: &str,
+        token: TokenInfo<T>,
+        held: Uint128,
+    ) -> Result<(TokenInfo<T>, Uint128), ContractError> {
+        let settled = self.token_rewards(deps.storage, token.reward_claimed)?;
+        if settled.is_zero() {
+            return Ok((token, settled));
+        }
+        self.assert_not_paused(deps.storage, "claims", |p| p.claims)?;
+        self._assert_reward_balance(deps.as_ref(), env, settled + held)?;
+        self._record_token_claim(deps.storage, token_id, token, settled)?;
+        Ok((self.tokens.load(deps.storage, token_id)?, settled))
+    }
+
+    /// Settles a sale whose `price`, in the rewards denom, is already held by the contract:
+    /// pays royalties, pays the seller their proceeds and the `settled` rewards of the token,
+    /// and hands the token to the buyer. The token may be held by the contract for the seller.
+    #[allow(clippy::too_many_arguments)]
+    pub fn _complete_sale(
+        &self,
+        mut deps: DepsMut,
+        env: &Env,
+        token_id: &str,
+        token: TokenInfo<T>,
+        seller: Addr,
+        buyer: Addr,
+        price: &Coin,
+        settled: Uint128,
+    ) -> Result<Response<ArchwayMsg>, ContractError> {
+        self.assert_can_move(deps.storage, &seller, token_id, &token, &buyer)?;
+        // an escrowed token still counts for its seller
+        self.decrement_balance(deps.storage, &seller)?;
+        self.increment_balance(deps.storage, &buyer)?;
+        self._set_owner(deps.storage, token_id, token, buyer)?;
+
+        let (mut messages, royalty) = self._pay_royalties(deps.branch(), env, token_id, price)?;
+        let proceeds = price
+            .amount
+            .checked_sub(royalty)
+            .map_err(StdError::overflow)?
+            + settled;
+        if !proceeds.is_zero() {
+            messages.push(BankMsg::Send {
+                to_address: seller.to_string(),
+                amount: coins(proceeds.u128(), &price.denom),
+            });
+        }
+
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_attribute("seller", seller)
+            .add_attribute("royalty", royalty)
+            .add_attribute("settled_rewards", settled))
+    }
+
+    /// Gives an escrowed token back to the `seller` it is held for. This only undoes the
+    /// escrow, so pauses, locks and the blocklist don't apply.
+    fn _return_escrowed(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        token: TokenInfo<T>,
+        seller: Addr,
+    ) -> Result<(), ContractError> {
+        self._set_owner(storage, token_id, token, seller)?;
+        Ok(())
+    }
+
+    /// Pays each royalty recipient of a sale, crediting the holders' share to the reward pool
+    pub fn _pay_royalties(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        token_id: &str,
+        price: &Coin,
+    ) -> Result<(Vec<BankMsg>, Uint128), ContractError> {
+        let splits = self
+            .query_royalty_splits(
+                deps.as_ref(),
+                env.clone(),
+                token_id.to_string(),
+                price.amount,
+            )?
+            .splits;
+
+        let mut messages = vec![];
+        let mut total = Uint128::zero();
+        for split in splits {
+            if split.amount.is_zero() {
+                continue;
+            }
+            total += split.amount;
+            if split.address == env.contract.address.as_str() {
+                self.add_total_arch_reward(deps.storage, split.amount.u128())?;
+            } else {
+                messages.push(BankMsg::Send {
+                    to_address: split.address,
+                    amount: coins(split.amount.u128(), &price.denom),
+                });
+            }
+        }
+        Ok((messages, total))
+    }
+
+    /// Checks for `sender` moving a token to `recipient`, beside their permission to send it
+    pub fn assert_can_move(
+        &self,
+        storage: &dyn Storage,
+        sender: &Addr,
+        token_id: &str,
+        token: &TokenInfo<T>,
+        recipient: &Addr,
+    ) -> Result<(), ContractError> {
+        self.assert_not_paused(storage, "transfers", |p| p.transfers)?;
+        self.assert_transferable(storage, Some(token_id))?;
+        for addr in [sender, &token.owner, recipient] {
+            self.assert_not_blocked(storage, addr)?;
+        }
+        Ok(())
+    }
+
+    /// Hands a token to `recipient` once `sender` is known to be allowed to move it
+    pub fn _move_token(
+        &self,
+        storage: &mut dyn Storage,
+        sender: &Addr,
+        token_id: &str,
+        mut token: TokenInfo<T>,
+        recipient: Addr,
+    ) -> Result<TokenInfo<T>, ContractError> {
+        self.assert_can_move(storage, sender, token_id, &token, &recipient)?;
+        self.decrement_balance(storage, &token.owner)?;
+        self.increment_balance(storage, &recipient)?;
+        self._set_owner(storage, token_id, token, recipient)
+    }
+
+    /// Records `owner` as the holder of a token, leaving the balances to the caller
+    fn _set_owner(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        mut token: TokenInfo<T>,
+        owner: Addr,
+    ) -> Result<TokenInfo<T>, ContractError> {
         // set owner and remove existing approvals
-        token.owner = deps.api.addr_validate(recipient)?;
+        token.owner = owner;
         token.approvals = vec![];
-        self.tokens.save(deps.storage, token_id, &token)?;
+        self.tokens.save(storage, token_id, &token)?;
+        // a listing is made by the previous owner
+        self.listings.remove(storage, token_id)?;
         Ok(token)
     }
 
@@ -398,6 +2275,8 @@ where
 
         // only difference between approve and revoke
         if add {
+            // revoking stays possible on non-transferable tokens
+            self.assert_transferable(deps.storage, Some(token_id))?;
             // reject expired data as invalid
             let expires = expires.unwrap_or_default();
             if expires.is_expired(&env.block) {
@@ -9,8 +9,9 @@ pub mod state;
 mod contract_tests;
 
 pub use crate::error::ContractError;
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MinterResponse, QueryMsg};
+pub use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, MinterResponse, QueryMsg};
 pub use crate::state::Cw721Contract;
+use crate::state::RoyaltyExtension;
 
 use cosmwasm_schema::cw_serde;
 pub use cw_ownable::{Action, Ownership, OwnershipError};
@@ -20,6 +21,8 @@ use cosmwasm_std::Empty;
 // Version info for migration
 pub const CONTRACT_NAME: &str = "crates.io:cw721-rewards";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// First version keeping the token totals `migrate` backfills for older contracts
+const TOKEN_TOTALS_VERSION: (u64, u64, u64) = (0, 18, 0);
 
 const REWARDS_WITHDRAW_REPLY: u64 = 1001;
 
@@ -47,8 +50,42 @@ pub struct Metadata {
     pub background_color: Option<String>,
     pub animation_url: Option<String>,
     pub youtube_url: Option<String>,
+    /// Whole percent, superseded by `royalty_bps` when both are set
     pub royalty_percentage: Option<u64>,
     pub royalty_payment_address: Option<String>,
+    /// Royalty in basis points, 250 is 2.5%
+    pub royalty_bps: Option<u64>,
+    /// How the royalty is divided among collaborators. `royalty_payment_address` receives
+    /// the whole royalty and is expected to forward these shares.
+    pub royalty_splits: Option<Vec<RoyaltySplit>>,
+}
+
+#[cw_serde]
+pub struct RoyaltySplit {
+    pub address: String,
+    /// Basis points of the sale price, the shares of a token add up to its royalty
+    pub share_bps: u64,
+}
+
+impl Metadata {
+    /// The token's royalty in basis points, if it sets one
+    pub fn royalty_bps(&self) -> Option<u64> {
+        self.royalty_bps.or_else(|| {
+            self.royalty_percentage
+                .map(|percentage| percentage.saturating_mul(100))
+        })
+    }
+
+    /// Whether the token sets a royalty along with an address to pay it to
+    pub fn has_payable_royalty(&self) -> bool {
+        self.royalty_bps().unwrap_or_default() > 0 && self.royalty_payment_address.is_some()
+    }
+}
+
+impl RoyaltyExtension for Extension {
+    fn has_payable_royalty(&self) -> bool {
+        self.as_ref().map_or(false, Metadata::has_payable_royalty)
+    }
 }
 
 pub type Extension = Option<Metadata>;
@@ -61,7 +98,7 @@ pub mod entry {
     #[cfg(not(feature = "library"))]
     use cosmwasm_std::entry_point;
     use cosmwasm_std::{
-        Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult,
+        Api, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult,
     };
 
     // This makes a conscious choice on the various generics used by the contract
@@ -86,25 +123,70 @@ pub mod entry {
         msg: ExecuteMsg<Extension>,
     ) -> Result<Response<ArchwayMsg>, ContractError> {
         let tract = Cw721Contract::<Extension, ArchwayMsg, Empty, Empty>::default();
+        let max_royalty_bps = tract.max_royalty(deps.storage)?;
 
-        if let ExecuteMsg::Mint {
-            extension:
-                Some(Metadata {
-                    royalty_percentage: Some(royalty_percentage),
-                    ..
-                }),
-            ..
-        } = &msg
-        {
-            // validate royalty_percentage to be between 0 and 100
-            // no need to check < 0 because royalty_percentage is u64
-            if *royalty_percentage > 100 {
-                return Err(ContractError::InvalidRoyaltyPercentage);
+        match &msg {
+            ExecuteMsg::Mint { extension, .. }
+            | ExecuteMsg::MintNext { extension, .. }
+            | ExecuteMsg::PublicMint { extension, .. }
+            | ExecuteMsg::WhitelistMint { extension, .. }
+            | ExecuteMsg::UpdateNftInfo { extension, .. } => {
+                validate_royalty(deps.api, max_royalty_bps, extension)?
             }
+            ExecuteMsg::BatchMint { mints } => {
+                for item in mints {
+                    validate_royalty(deps.api, max_royalty_bps, &item.extension)?;
+                }
+            }
+            _ => {}
         }
+
         tract.execute(deps, env, info, msg)
     }
 
+    fn validate_royalty(
+        api: &dyn Api,
+        max_royalty_bps: u64,
+        extension: &Extension,
+    ) -> Result<(), ContractError> {
+        if let Some(metadata) = extension {
+            // no need to check < 0 because royalties are u64
+            if metadata.royalty_bps().unwrap_or_default() > max_royalty_bps {
+                return Err(ContractError::InvalidRoyaltyPercentage);
+            }
+            if let Some(address) = &metadata.royalty_payment_address {
+                api.addr_validate(address)?;
+            }
+            if let Some(splits) = &metadata.royalty_splits {
+                validate_royalty_splits(api, metadata, splits)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_royalty_splits(
+        api: &dyn Api,
+        metadata: &Metadata,
+        splits: &[RoyaltySplit],
+    ) -> Result<(), ContractError> {
+        if metadata.royalty_payment_address.is_none() {
+            return Err(ContractError::InvalidRoyaltySplits {
+                reason: "royalty_payment_address must be set to the splitter".to_string(),
+            });
+        }
+        let mut total: u64 = 0;
+        for split in splits {
+            api.addr_validate(&split.address)?;
+            total = total.saturating_add(split.share_bps);
+        }
+        if splits.is_empty() || metadata.royalty_bps() != Some(total) {
+            return Err(ContractError::InvalidRoyaltySplits {
+                reason: "shares must add up to the token royalty".to_string(),
+            });
+        }
+        Ok(())
+    }
+
     #[cfg_attr(not(feature = "library"), entry_point)]
     pub fn query(deps: Deps, env: Env, msg: QueryMsg<Cw2981QueryMsg>) -> StdResult<Binary> {
         let tract = Cw721Contract::<Extension, Empty, Empty, Cw2981QueryMsg>::default();
@@ -112,26 +194,47 @@ pub mod entry {
     }
 
     #[cfg_attr(not(feature = "library"), entry_point)]
-    pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
         match msg.id {
             REWARDS_WITHDRAW_REPLY => rewards::after_rewards_withdrawn(deps, msg),
-            id => Err(StdError::not_found(format!("Unknown reply id: {}", id))),
+            id => Err(StdError::not_found(format!("Unknown reply id: {}", id)).into()),
         }
     }
 
     #[cfg_attr(not(feature = "library"), entry_point)]
-    pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-        Ok(Response::default())
+    pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+        let stored = cw2::get_contract_version(deps.storage)?;
+        // older contracts only stored the tokens, their totals are counted once
+        if parse_version(&stored.version) < TOKEN_TOTALS_VERSION {
+            let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+            tract.backfill_token_totals(deps.storage)?;
+        }
+        cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "migrate")
+            .add_attribute("from_version", stored.version))
+    }
+
+    /// `(major, minor, patch)` of a stored contract version, any pre-release suffix aside
+    fn parse_version(version: &str) -> (u64, u64, u64) {
+        let mut parts = version
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|part| part.parse().unwrap_or_default());
+        let mut next = || parts.next().unwrap_or_default();
+        (next(), next(), next())
     }
 }
 
 pub mod rewards {
 
-    use cosmwasm_std::{Binary, SubMsgResponse};
+    use cosmwasm_std::{coins, BankMsg, Binary, SubMsgResponse, Uint128};
+
+    use crate::state::WithdrawConfig;
 
     use super::*;
 
-    pub fn after_rewards_withdrawn(deps: DepsMut, msg: Reply) -> StdResult<Response> {
+    pub fn after_rewards_withdrawn(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
         let tract = Cw721Contract::<Extension, ArchwayMsg, Empty, Empty>::default();
 
         let data = parse_reply_data(msg)?;
@@ -142,6 +245,15 @@ pub mod rewards {
         let mut rewards_balance = NativeBalance(withdraw_response.total_rewards);
         rewards_balance.normalize();
 
+        // the bounty is paid in the rewards denom, so only those rewards count towards it
+        let rewards_denom = tract.rewards_denom.load(deps.storage)?;
+        let denom_rewards = rewards_balance
+            .0
+            .iter()
+            .find(|coin| coin.denom == rewards_denom)
+            .map(|coin| coin.amount)
+            .unwrap_or_default();
+
         let total_rewards: Vec<String> = rewards_balance
             .clone()
             .into_vec()
@@ -152,16 +264,46 @@ pub mod rewards {
         let total_rewards_u128: u128 = rewards_balance
             .into_vec()
             .iter()
-            .map(|coin| coin.amount.u128())
-            .sum();
+            .try_fold(Uint128::zero(), |acc, coin| acc.checked_add(coin.amount))
+            .map_err(|_| ContractError::RewardOverflow {})?
+            .u128();
 
-        tract.add_total_arch_reward(deps.storage, total_rewards_u128)?;
+        // the keeper bounty comes out of the withdrawn rewards, the rest goes to the tokens
+        let caller = tract.rewards_withdraw_caller.may_load(deps.storage)?;
+        tract.rewards_withdraw_caller.remove(deps.storage);
+        let bounty = match (&caller, tract.withdraw_config.may_load(deps.storage)?) {
+            (
+                Some(_),
+                Some(WithdrawConfig {
+                    keeper_bounty: Some(bounty),
+                    ..
+                }),
+            ) => bounty.amount(denom_rewards),
+            _ => Uint128::zero(),
+        };
+        let distributed = Uint128::new(total_rewards_u128)
+            .checked_sub(bounty)
+            .map_err(|_| ContractError::RewardOverflow {})?;
+
+        tract.add_total_arch_reward(deps.storage, distributed.u128())?;
 
-        let res = Response::new()
+        let mut res = Response::new()
             .add_attribute("method", "after_rewards_withdrawn")
             .add_attribute("records_num", withdraw_response.records_num.to_string())
             .add_attribute("total_rewards", total_rewards.concat());
 
+        if let Some(caller) = caller {
+            if !bounty.is_zero() {
+                res = res
+                    .add_message(BankMsg::Send {
+                        to_address: caller.to_string(),
+                        amount: coins(bounty.u128(), rewards_denom),
+                    })
+                    .add_attribute("keeper", caller)
+                    .add_attribute("bounty", bounty.to_string());
+            }
+        }
+
         Ok(res)
     }
 
@@ -179,16 +321,20 @@ pub mod rewards {
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::{
-        from_binary,
+        coins, from_binary,
         testing::{mock_dependencies, mock_env, mock_info},
-        Uint128,
+        Addr, BankMsg, Binary, CosmosMsg, Decimal, Deps, SubMsgResponse, SubMsgResult, Uint128,
     };
     use cw2::ContractVersion;
     use cw721::Cw721Query;
 
     const CREATOR: &str = "creator";
 
-    use crate::msg::{CheckRoyaltiesResponse, Cw2981QueryMsg, RoyaltiesInfoResponse};
+    use crate::msg::{
+        CheckRoyaltiesResponse, Cw2981QueryMsg, MigrateMsg, MintItem, RoyaltiesInfoResponse,
+        RoyaltySplitAmount, RoyaltySplitsResponse, TraitCount, TraitCountsResponse,
+    };
+    use crate::state::KeeperBounty;
 
     use super::*;
 
@@ -206,6 +352,9 @@ mod tests {
                 symbol: "".into(),
                 minter: "larry".into(),
                 rewards_denom: "aconst".into(),
+                base_token_uri: None,
+                max_supply: None,
+                transferable: None,
             },
         )
         .unwrap();
@@ -231,6 +380,9 @@ mod tests {
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
             rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -254,6 +406,65 @@ mod tests {
         assert_eq!(res.extension, extension);
     }
 
+    #[test]
+    fn update_nft_info_validates_royalty() {
+        let mut deps = mock_dependencies();
+        let contract = Cw721Contract::<Extension, Empty, Empty, Cw2981QueryMsg>::default();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let token_id = "Enterprise";
+        let token_uri = Some("https://starships.example.com/Starship/Enterprise.json".into());
+        let extension = Some(Metadata {
+            description: Some("Spaceship with Warp Drive".into()),
+            name: Some("Starship USS Enterprise".to_string()),
+            ..Metadata::default()
+        });
+        let exec_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: "john".to_string(),
+            token_uri: token_uri.clone(),
+            extension: extension.clone(),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+
+        // updates go through the same royalty validation as mints
+        let exec_msg = ExecuteMsg::UpdateNftInfo {
+            token_id: token_id.to_string(),
+            token_uri: token_uri.clone(),
+            extension: Some(Metadata {
+                royalty_percentage: Some(101),
+                ..extension.clone().unwrap()
+            }),
+        };
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRoyaltyPercentage);
+
+        let extension = Some(Metadata {
+            royalty_percentage: Some(5),
+            royalty_payment_address: Some("john".to_string()),
+            ..extension.unwrap()
+        });
+        let exec_msg = ExecuteMsg::UpdateNftInfo {
+            token_id: token_id.to_string(),
+            token_uri,
+            extension: extension.clone(),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
+        let res = contract.nft_info(deps.as_ref(), token_id.into()).unwrap();
+        assert_eq!(res.extension, extension);
+    }
+
     #[test]
     fn validate_royalty_information() {
         let mut deps = mock_dependencies();
@@ -265,6 +476,9 @@ mod tests {
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
             rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -285,6 +499,45 @@ mod tests {
         assert_eq!(err, ContractError::InvalidRoyaltyPercentage);
     }
 
+    #[test]
+    fn validate_batch_royalty_information() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let exec_msg = ExecuteMsg::BatchMint {
+            mints: vec![
+                MintItem {
+                    token_id: "Enterprise".to_string(),
+                    owner: "john".to_string(),
+                    token_uri: None,
+                    extension: None,
+                },
+                MintItem {
+                    token_id: "Voyager".to_string(),
+                    owner: "john".to_string(),
+                    token_uri: None,
+                    extension: Some(Metadata {
+                        royalty_percentage: Some(101),
+                        ..Metadata::default()
+                    }),
+                },
+            ],
+        };
+        let err = entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRoyaltyPercentage);
+    }
+
     #[test]
     fn check_royalties_response() {
         let mut deps = mock_dependencies();
@@ -296,6 +549,9 @@ mod tests {
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
             rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -310,7 +566,23 @@ mod tests {
                 ..Metadata::default()
             }),
         };
-        entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+
+        // no token sets a royalty and there is no collection default
+        let res = contract.check_royalties(deps.as_ref()).unwrap();
+        assert!(!res.royalty_payments);
+
+        let exec_msg = ExecuteMsg::Mint {
+            token_id: "Voyager".to_string(),
+            owner: "john".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("john".to_string()),
+                royalty_bps: Some(250),
+                ..Metadata::default()
+            }),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
 
         let expected = CheckRoyaltiesResponse {
             royalty_payments: true,
@@ -325,6 +597,66 @@ mod tests {
         let query_res: CheckRoyaltiesResponse =
             from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
         assert_eq!(query_res, expected);
+
+        // removing the only token royalty turns royalties off again
+        let exec_msg = ExecuteMsg::UpdateNftInfo {
+            token_id: "Voyager".to_string(),
+            token_uri: None,
+            extension: None,
+        };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        let res = contract.check_royalties(deps.as_ref()).unwrap();
+        assert!(!res.royalty_payments);
+
+        // a royalty without anyone to pay it to can't be paid
+        let exec_msg = ExecuteMsg::Mint {
+            token_id: "Defiant".to_string(),
+            owner: "john".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_bps: Some(250),
+                ..Metadata::default()
+            }),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        let res = contract.check_royalties(deps.as_ref()).unwrap();
+        assert!(!res.royalty_payments);
+
+        // contracts importing the library keep the count up to date too
+        let exec_msg = ExecuteMsg::Mint {
+            token_id: "Discovery".to_string(),
+            owner: "john".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("john".to_string()),
+                royalty_bps: Some(250),
+                ..Metadata::default()
+            }),
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), info.clone(), exec_msg)
+            .unwrap();
+        let res = contract.check_royalties(deps.as_ref()).unwrap();
+        assert!(res.royalty_payments);
+        let exec_msg = ExecuteMsg::UpdateNftInfo {
+            token_id: "Discovery".to_string(),
+            token_uri: None,
+            extension: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), info.clone(), exec_msg)
+            .unwrap();
+        let res = contract.check_royalties(deps.as_ref()).unwrap();
+        assert!(!res.royalty_payments);
+
+        // a collection default is enough
+        let exec_msg = ExecuteMsg::SetDefaultRoyalty {
+            payment_address: "starfleet".to_string(),
+            share: Decimal::percent(5),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
+        let res = contract.check_royalties(deps.as_ref()).unwrap();
+        assert!(res.royalty_payments);
     }
 
     #[test]
@@ -338,6 +670,9 @@ mod tests {
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
             rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -362,7 +697,12 @@ mod tests {
             royalty_amount: Uint128::new(10),
         };
         let res = contract
-            .query_royalties_info(deps.as_ref(), token_id.to_string(), Uint128::new(100))
+            .query_royalties_info(
+                deps.as_ref(),
+                mock_env(),
+                token_id.to_string(),
+                Uint128::new(100),
+            )
             .unwrap();
         assert_eq!(res, expected);
 
@@ -405,10 +745,609 @@ mod tests {
         let res = contract
             .query_royalties_info(
                 deps.as_ref(),
+                mock_env(),
                 voyager_token_id.to_string(),
                 Uint128::new(43),
             )
             .unwrap();
         assert_eq!(res, voyager_expected);
     }
+
+    #[test]
+    fn basis_point_royalties() {
+        let mut deps = mock_dependencies();
+
+        let contract = Cw721Contract::<Extension, Empty, Empty, Cw2981QueryMsg>::default();
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        // metadata stored before basis points still deserializes
+        let legacy: Metadata = cosmwasm_std::from_slice(
+            br#"{"image":null,"image_data":null,"external_url":null,"description":null,"name":null,"attributes":null,"background_color":null,"animation_url":null,"youtube_url":null,"royalty_percentage":4,"royalty_payment_address":"janeway"}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.royalty_bps(), Some(400));
+
+        let exec_msg = ExecuteMsg::UpdateMaxRoyalty {
+            max_royalty_bps: 1_000,
+        };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+
+        // both representations are checked against the maximum
+        for extension in [
+            Metadata {
+                royalty_bps: Some(1_001),
+                ..Metadata::default()
+            },
+            Metadata {
+                royalty_percentage: Some(11),
+                ..Metadata::default()
+            },
+        ] {
+            let exec_msg = ExecuteMsg::Mint {
+                token_id: "Defiant".to_string(),
+                owner: "sisko".to_string(),
+                token_uri: None,
+                extension: Some(extension),
+            };
+            let err =
+                entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidRoyaltyPercentage);
+        }
+
+        let exec_msg = ExecuteMsg::Mint {
+            token_id: "Defiant".to_string(),
+            owner: "sisko".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("sisko".to_string()),
+                royalty_bps: Some(250),
+                ..Metadata::default()
+            }),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
+
+        let res = contract
+            .query_royalties_info(
+                deps.as_ref(),
+                mock_env(),
+                "Defiant".to_string(),
+                Uint128::new(1_000),
+            )
+            .unwrap();
+        assert_eq!(res.royalty_amount, Uint128::new(25));
+        // 43 x 2.5% is 1.075, rounded down
+        let res = contract
+            .query_royalties_info(
+                deps.as_ref(),
+                mock_env(),
+                "Defiant".to_string(),
+                Uint128::new(43),
+            )
+            .unwrap();
+        assert_eq!(res.royalty_amount, Uint128::new(1));
+    }
+
+    #[test]
+    fn royalty_splits() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let split = |address: &str, share_bps: u64| RoyaltySplit {
+            address: address.to_string(),
+            share_bps,
+        };
+        let mint =
+            |royalty_payment_address: Option<&str>, splits: Vec<RoyaltySplit>| ExecuteMsg::Mint {
+                token_id: "Enterprise".to_string(),
+                owner: "jeanluc".to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    royalty_payment_address: royalty_payment_address.map(String::from),
+                    royalty_bps: Some(500),
+                    royalty_splits: Some(splits),
+                    ..Metadata::default()
+                }),
+            };
+
+        // shares must add up to the royalty and be paid through a splitter
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            mint(
+                Some("splitter"),
+                vec![split("scotty", 300), split("geordi", 100)],
+            ),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRoyaltySplits { .. }));
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            mint(None, vec![split("scotty", 300), split("geordi", 200)]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRoyaltySplits { .. }));
+
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            mint(
+                Some("splitter"),
+                vec![split("scotty", 300), split("geordi", 200)],
+            ),
+        )
+        .unwrap();
+
+        // CW2981 callers pay the splitter
+        let query_msg = QueryMsg::Extension {
+            msg: Cw2981QueryMsg::RoyaltyInfo {
+                token_id: "Enterprise".to_string(),
+                sale_price: Uint128::new(1_030),
+            },
+        };
+        let res: RoyaltiesInfoResponse =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.address, "splitter");
+        assert_eq!(res.royalty_amount, Uint128::new(51));
+
+        // 30.9 and 20.6 round down, the dust goes to the first recipient
+        let query_msg = QueryMsg::Extension {
+            msg: Cw2981QueryMsg::RoyaltySplits {
+                token_id: "Enterprise".to_string(),
+                sale_price: Uint128::new(1_030),
+            },
+        };
+        let res: RoyaltySplitsResponse =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res.splits,
+            vec![
+                RoyaltySplitAmount {
+                    address: "scotty".to_string(),
+                    amount: Uint128::new(31),
+                },
+                RoyaltySplitAmount {
+                    address: "geordi".to_string(),
+                    amount: Uint128::new(20),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn default_royalty() {
+        let mut deps = mock_dependencies();
+
+        let contract = Cw721Contract::<Extension, Empty, Empty, Cw2981QueryMsg>::default();
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        // royalty addresses are validated at mint
+        let exec_msg = ExecuteMsg::Mint {
+            token_id: "Defiant".to_string(),
+            owner: "sisko".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("Sisko".to_string()),
+                royalty_percentage: Some(10),
+                ..Metadata::default()
+            }),
+        };
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+
+        let mints = [
+            ("Enterprise", None),
+            (
+                "Voyager",
+                Some(Metadata {
+                    royalty_payment_address: Some("janeway".to_string()),
+                    royalty_percentage: Some(10),
+                    ..Metadata::default()
+                }),
+            ),
+        ];
+        for (token_id, extension) in mints {
+            let exec_msg = ExecuteMsg::Mint {
+                token_id: token_id.to_string(),
+                owner: "jeanluc".to_string(),
+                token_uri: None,
+                extension,
+            };
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        }
+
+        // nothing is owed without a royalty
+        let res = contract
+            .query_royalties_info(
+                deps.as_ref(),
+                mock_env(),
+                "Enterprise".to_string(),
+                Uint128::new(100),
+            )
+            .unwrap();
+        assert_eq!(
+            res,
+            RoyaltiesInfoResponse {
+                address: "".to_string(),
+                royalty_amount: Uint128::zero(),
+            }
+        );
+
+        let exec_msg = ExecuteMsg::SetDefaultRoyalty {
+            payment_address: "starfleet".to_string(),
+            share: Decimal::percent(5),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
+
+        let res = contract
+            .query_royalties_info(
+                deps.as_ref(),
+                mock_env(),
+                "Enterprise".to_string(),
+                Uint128::new(100),
+            )
+            .unwrap();
+        assert_eq!(
+            res,
+            RoyaltiesInfoResponse {
+                address: "starfleet".to_string(),
+                royalty_amount: Uint128::new(5),
+            }
+        );
+
+        // tokens with their own royalty keep it
+        let res = contract
+            .query_royalties_info(
+                deps.as_ref(),
+                mock_env(),
+                "Voyager".to_string(),
+                Uint128::new(100),
+            )
+            .unwrap();
+        assert_eq!(
+            res,
+            RoyaltiesInfoResponse {
+                address: "janeway".to_string(),
+                royalty_amount: Uint128::new(10),
+            }
+        );
+    }
+
+    #[test]
+    fn trait_index() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let attributes = |traits: &[(&str, &str)]| {
+            Some(Metadata {
+                attributes: Some(
+                    traits
+                        .iter()
+                        .map(|(trait_type, value)| Trait {
+                            display_type: None,
+                            trait_type: trait_type.to_string(),
+                            value: value.to_string(),
+                        })
+                        .collect(),
+                ),
+                ..Metadata::default()
+            })
+        };
+        let mints = vec![
+            (
+                "Enterprise",
+                attributes(&[("Background", "Gold"), ("Drive", "Warp")]),
+            ),
+            ("Voyager", attributes(&[("Background", "Gold")])),
+            // repeated attributes count once
+            (
+                "Defiant",
+                attributes(&[("Drive", "Warp"), ("Drive", "Warp")]),
+            ),
+            ("Shuttle", None),
+        ];
+        for (token_id, extension) in mints {
+            let exec_msg = ExecuteMsg::Mint {
+                token_id: token_id.to_string(),
+                owner: "john".to_string(),
+                token_uri: None,
+                extension,
+            };
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        }
+
+        // the index follows metadata updates
+        let exec_msg = ExecuteMsg::UpdateNftInfo {
+            token_id: "Voyager".to_string(),
+            token_uri: None,
+            extension: attributes(&[("Background", "Blue")]),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+
+        // transfers leave the traits alone
+        let exec_msg = ExecuteMsg::TransferNft {
+            recipient: "jane".to_string(),
+            token_id: "Enterprise".to_string(),
+        };
+        entry::execute(deps.as_mut(), mock_env(), mock_info("john", &[]), exec_msg).unwrap();
+
+        let tokens_by_trait = |deps: Deps, trait_type: &str, value: &str| -> Vec<String> {
+            let query_msg = QueryMsg::TokensByTrait {
+                trait_type: trait_type.to_string(),
+                value: value.to_string(),
+                start_after: None,
+                limit: None,
+            };
+            let res: cw721::TokensResponse =
+                from_binary(&entry::query(deps, mock_env(), query_msg).unwrap()).unwrap();
+            res.tokens
+        };
+        assert_eq!(
+            tokens_by_trait(deps.as_ref(), "Background", "Gold"),
+            vec!["Enterprise"]
+        );
+        assert_eq!(
+            tokens_by_trait(deps.as_ref(), "Background", "Blue"),
+            vec!["Voyager"]
+        );
+        assert_eq!(
+            tokens_by_trait(deps.as_ref(), "Drive", "Warp"),
+            vec!["Defiant", "Enterprise"]
+        );
+
+        let trait_counts_page = |deps: Deps,
+                                 start_after: Option<(&str, &str)>,
+                                 limit: Option<u32>|
+         -> Vec<TraitCount> {
+            let query_msg = QueryMsg::TraitCounts {
+                start_after: start_after
+                    .map(|(trait_type, value)| (trait_type.to_string(), value.to_string())),
+                limit,
+            };
+            let res: TraitCountsResponse =
+                from_binary(&entry::query(deps, mock_env(), query_msg).unwrap()).unwrap();
+            res.traits
+        };
+        let trait_counts = |deps: Deps| trait_counts_page(deps, None, None);
+        let count = |trait_type: &str, value: &str, count: u64| TraitCount {
+            trait_type: trait_type.to_string(),
+            value: value.to_string(),
+            count,
+        };
+        let expected = vec![
+            count("Background", "Blue", 1),
+            count("Background", "Gold", 1),
+            count("Drive", "Warp", 2),
+        ];
+        assert_eq!(trait_counts(deps.as_ref()), expected);
+        assert_eq!(
+            trait_counts_page(deps.as_ref(), None, Some(2)),
+            expected[..2]
+        );
+        assert_eq!(
+            trait_counts_page(deps.as_ref(), Some(("Background", "Gold")), None),
+            expected[2..]
+        );
+
+        // migrating lists tokens minted before traits were tracked, and can be repeated
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        for token_id in ["Enterprise", "Defiant"] {
+            let token = tract.tokens.load(&deps.storage, token_id).unwrap();
+            tract
+                .remove_token_traits(&mut deps.storage, token_id, &token.extension)
+                .unwrap();
+        }
+        assert_eq!(
+            trait_counts(deps.as_ref()),
+            vec![count("Background", "Blue", 1)]
+        );
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.17.0").unwrap();
+        for _ in 0..2 {
+            entry::migrate(deps.as_mut(), mock_env(), MigrateMsg::Migrate {}).unwrap();
+        }
+        assert_eq!(trait_counts(deps.as_ref()), expected);
+        assert_eq!(
+            tokens_by_trait(deps.as_ref(), "Drive", "Warp"),
+            vec!["Defiant", "Enterprise"]
+        );
+    }
+
+    #[test]
+    fn keeper_bounty_paid_on_reply() {
+        let mut deps = mock_dependencies();
+        let contract = Cw721Contract::<Extension, Empty, Empty, Cw2981QueryMsg>::default();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        // 1% of the withdrawn rewards, capped at 5
+        let config_msg = ExecuteMsg::UpdateWithdrawConfig {
+            min_interval: None,
+            min_pending_rewards: None,
+            keepers: None,
+            keeper_bounty: Some(KeeperBounty {
+                bps: 100,
+                cap: Uint128::new(5),
+            }),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, config_msg).unwrap();
+
+        let withdraw_msg = ExecuteMsg::WithdrawRewards {};
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("keeper", &[]),
+            withdraw_msg,
+        )
+        .unwrap();
+
+        let reply = |data: &[u8]| Reply {
+            id: REWARDS_WITHDRAW_REPLY,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(data)),
+            }),
+        };
+        let res = entry::reply(
+            deps.as_mut(),
+            mock_env(),
+            reply(br#"{"records_num":1,"total_rewards":[{"denom":"aconst","amount":"1000"}]}"#),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "keeper".to_string(),
+                amount: coins(5, "aconst"),
+            })
+        );
+
+        // the bounty is not distributed to the tokens
+        let total = contract
+            .get_total_arch_rewards(deps.as_ref(), None)
+            .unwrap();
+        assert_eq!(total.total_arch_reward, 995);
+
+        // rewards in other denoms don't raise the bounty
+        let withdraw_msg = ExecuteMsg::WithdrawRewards {};
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("keeper", &[]),
+            withdraw_msg,
+        )
+        .unwrap();
+        let res = entry::reply(
+            deps.as_mut(),
+            mock_env(),
+            reply(
+                br#"{"records_num":1,"total_rewards":[{"denom":"aconst","amount":"200"},{"denom":"uarch","amount":"100000"}]}"#,
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "keeper".to_string(),
+                amount: coins(2, "aconst"),
+            })
+        );
+    }
+
+    #[test]
+    fn migrate_backfills_token_totals() {
+        let mut deps = mock_dependencies();
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            rewards_denom: "aconst".to_string(),
+            base_token_uri: None,
+            max_supply: None,
+            transferable: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+        let royalty = Some(Metadata {
+            royalty_bps: Some(500),
+            royalty_payment_address: Some("jeanluc".to_string()),
+            ..Metadata::default()
+        });
+        for (token_id, extension) in [("Enterprise", royalty), ("Voyager", None)] {
+            let exec_msg = ExecuteMsg::Mint {
+                token_id: token_id.to_string(),
+                owner: "john".to_string(),
+                token_uri: None,
+                extension,
+            };
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        }
+
+        // a contract from before the totals were kept only has its tokens
+        for (token_id, claimed) in [("Enterprise", 30), ("Voyager", 12)] {
+            tract
+                .tokens
+                .update(&mut deps.storage, token_id, |token| -> StdResult<_> {
+                    let mut token = token.unwrap();
+                    token.reward_claimed = claimed;
+                    Ok(token)
+                })
+                .unwrap();
+        }
+        tract.total_rewards_claimed.remove(&mut deps.storage);
+        tract.royalty_token_count.remove(&mut deps.storage);
+        tract
+            .balances
+            .remove(&mut deps.storage, &Addr::unchecked("john"));
+        tract.num_owners.remove(&mut deps.storage);
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.17.0").unwrap();
+
+        // migrating counts them once
+        for _ in 0..2 {
+            entry::migrate(deps.as_mut(), mock_env(), MigrateMsg::Migrate {}).unwrap();
+        }
+        assert_eq!(tract.total_rewards_claimed.load(&deps.storage).unwrap(), 42);
+        assert_eq!(tract.royalty_token_count.load(&deps.storage).unwrap(), 1);
+        let owner = Addr::unchecked("john");
+        assert_eq!(tract.balance(&deps.storage, &owner).unwrap(), 2);
+        assert_eq!(tract.num_owners(&deps.storage).unwrap(), 1);
+        let version = cw2::get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
 }
@@ -1,10 +1,13 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::Binary;
-use cosmwasm_std::{CustomMsg, Uint128};
+use cosmwasm_std::{Coin, CustomMsg, Decimal, HexBinary, Timestamp, Uint128};
 use cw721::Expiration;
 use cw_ownable::{cw_ownable_execute, cw_ownable_query};
+use cw_utils::Duration;
 use schemars::JsonSchema;
 
+use crate::state::{BlockReason, KeeperBounty, MintPhase};
+
 #[cw_serde]
 pub struct InstantiateMsg {
     /// Name of the NFT contract
@@ -13,6 +16,20 @@ pub struct InstantiateMsg {
     pub symbol: String,
     pub minter: String,
     pub rewards_denom: String,
+    /// Token uris not set at mint are derived as `{base_token_uri}/{token_id}.json`
+    pub base_token_uri: Option<String>,
+    /// Maximum number of tokens in existence at once, unlimited if unset
+    pub max_supply: Option<u64>,
+    /// Set to false to make every token soulbound, defaults to true
+    pub transferable: Option<bool>,
+}
+
+#[cw_serde]
+pub struct MintItem<T> {
+    pub token_id: String,
+    pub owner: String,
+    pub token_uri: Option<String>,
+    pub extension: T,
 }
 
 /// This is like Cw721ExecuteMsg but we add a Mint command for an owner
@@ -33,6 +50,69 @@ pub enum ExecuteMsg<T> {
         token_id: String,
         msg: Binary,
     },
+    /// Transfer several tokens to the same recipient. Fails as a whole if the sender
+    /// cannot transfer any one of them or a token is listed twice.
+    BatchTransferNft {
+        recipient: String,
+        token_ids: Vec<String>,
+    },
+    /// Send several tokens to a contract, triggering one `ReceiveNft` per token with
+    /// the same `msg`, in the given order. Fails as a whole if the sender cannot send
+    /// any one of them or a token is listed twice.
+    BatchSendNft {
+        contract: String,
+        token_ids: Vec<String>,
+        msg: Binary,
+    },
+    /// Offer a token for sale at a fixed price in the rewards denom. The token stays with
+    /// the owner, a transfer cancels the listing.
+    ListNft {
+        token_id: String,
+        price: Uint128,
+    },
+    /// Withdraw a listing, by the seller or the token's current owner
+    CancelListing {
+        token_id: String,
+    },
+    /// Buy a listed token, attaching exactly the listed price. Royalties are paid out of
+    /// the price and the seller also receives the token's unclaimed rewards.
+    BuyNft {
+        token_id: String,
+    },
+    /// Offer to buy a token for the attached rewards denom funds, held by the contract
+    /// until the offer is accepted or cancelled. Replaces and refunds an earlier offer.
+    MakeOffer {
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+    /// Sell a token to a bidder for their offer, by the owner or an approved operator.
+    /// Royalties are paid out of the offer.
+    AcceptOffer {
+        token_id: String,
+        bidder: String,
+    },
+    /// Withdraw an offer and refund it, also once it expired
+    CancelOffer {
+        token_id: String,
+    },
+    /// Auction a token in the rewards denom, by the owner or an approved operator.
+    /// The contract holds the token until the auction is settled, and the seller is paid the
+    /// token's unclaimed rewards.
+    StartAuction {
+        token_id: String,
+        reserve: Uint128,
+        duration: Duration,
+        min_increment: Uint128,
+    },
+    /// Bid on a running auction with the attached funds, refunding the previous bidder
+    Bid {
+        token_id: String,
+    },
+    /// Once an auction ends, sell the token to the highest bidder or return it
+    /// to the seller if nobody bid. Anyone can settle.
+    SettleAuction {
+        token_id: String,
+    },
     /// Allows operator to transfer / send the token from the owner's account.
     /// If expiration is set, then this allowance has a time/height limit
     Approve {
@@ -64,12 +144,172 @@ pub enum ExecuteMsg<T> {
         extension: T,
     },
 
+    /// Withdraw the contract's Archway rewards and distribute them to all tokens.
+    /// Subject to the restrictions in the withdraw config.
     WithdrawRewards {},
 
+    /// Mint several NFTs at once, can only be called by the contract minter
+    BatchMint {
+        mints: Vec<MintItem<T>>,
+    },
+
+    /// Mint a new NFT with the next sequential token id, can only be called by the contract minter
+    MintNext {
+        owner: String,
+        token_uri: Option<String>,
+        extension: T,
+    },
+
+    /// Minter or owner only, replace the token uri and extension of a minted token
+    UpdateNftInfo {
+        token_id: String,
+        token_uri: Option<String>,
+        extension: T,
+    },
+
+    /// Minter or owner only, permanently prevent metadata updates of a single token,
+    /// or of the whole collection if no token is given
+    FreezeMetadata {
+        token_id: Option<String>,
+    },
+
+    /// Owner only, hide token uris behind `placeholder_uri` until `Reveal`.
+    /// `provenance_hash` is the sha256 of the final base uri followed by a secret preimage.
+    SetRevealConfig {
+        placeholder_uri: String,
+        provenance_hash: HexBinary,
+    },
+
+    /// Owner only, publish the final base uri, checked against the provenance hash
+    Reveal {
+        base_uri: String,
+        provenance_preimage: String,
+    },
+
+    /// Owner only, set the royalty of tokens that don't set their own
+    SetDefaultRoyalty {
+        payment_address: String,
+        share: Decimal,
+    },
+
+    /// Owner only, remove the collection default royalty
+    RemoveDefaultRoyalty {},
+
+    /// Owner only, when `holders` is true royalties are reported as payable to this contract
+    /// and shared with token holders like Archway rewards
+    SetRoyaltyMode {
+        holders: bool,
+    },
+
+    /// Credit the attached rewards denom funds to the holder reward pool
+    ReceiveRoyalty {},
+
+    /// Credit rewards denom funds the contract holds beyond what it owes, such as royalties
+    /// paid with a plain bank send, to the holder reward pool
+    SweepRewards {},
+
+    /// Owner only, cap the royalty tokens and the collection default can set, in basis points
+    UpdateMaxRoyalty {
+        max_royalty_bps: u64,
+    },
+
+    /// Owner only, change the base uri of tokens without an explicit token uri
+    UpdateBaseTokenUri {
+        base_token_uri: String,
+    },
+
+    /// Owner only, permanently prevent changes to the base token uri
+    FreezeBaseTokenUri {},
+
+    /// Owner only, pause or resume groups of handlers. Every scope is set on each call.
+    SetPause {
+        minting: bool,
+        transfers: bool,
+        claims: bool,
+        withdrawals: bool,
+    },
+
+    /// Owner only, stop addresses from sending, receiving, minting and claiming tokens
+    BlockAddresses {
+        addresses: Vec<String>,
+        reason: String,
+    },
+
+    /// Owner only, lift a block
+    UnblockAddresses {
+        addresses: Vec<String>,
+    },
+
+    /// Owner only, prevent or allow transfers and approvals of a single token
+    SetTokenLock {
+        token_id: String,
+        locked: bool,
+    },
+
+    /// Mint a new NFT to the sender during the public sale, paying the configured price.
+    /// The token id is assigned by the contract.
+    PublicMint {
+        token_uri: Option<String>,
+        extension: T,
+    },
+
+    /// Mint a new NFT to the sender during the active allowlist phase, proving the sender
+    /// and its allocation are a leaf of the phase's Merkle root. The token id is assigned
+    /// by the contract.
+    WhitelistMint {
+        /// Number of tokens the sender can mint in the phase
+        allocation: u32,
+        proof: Vec<HexBinary>,
+        token_uri: Option<String>,
+        extension: T,
+    },
+
+    /// Owner only, adds or replaces an allowlist phase
+    SetMintPhase {
+        phase_id: u32,
+        /// sha256 Merkle root over `sha256(address ++ allocation)` leaves, the allocation as
+        /// 4 big-endian bytes, pairs hashed in sorted order
+        merkle_root: HexBinary,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        price: Coin,
+        treasury: Option<String>,
+    },
+
+    /// Owner only, removes an allowlist phase
+    RemoveMintPhase {
+        phase_id: u32,
+    },
+
+    /// Owner only, configures the public sale
+    SetMintConfig {
+        price: Coin,
+        per_address_limit: Option<u32>,
+        start_time: Option<Timestamp>,
+        end_time: Option<Timestamp>,
+        /// Receiver of the proceeds, if unset they are added to the reward pool
+        treasury: Option<String>,
+    },
+
+    /// Owner only, updates the restrictions on `WithdrawRewards`. Unset fields are kept,
+    /// a zero interval or amount, no keepers or a zero bounty lift that restriction.
+    UpdateWithdrawConfig {
+        min_interval: Option<Duration>,
+        min_pending_rewards: Option<Uint128>,
+        keepers: Option<Vec<String>>,
+        keeper_bounty: Option<KeeperBounty>,
+    },
+
     WithdrawTokenRewards {
         token_id: String,
     },
 
+    /// Withdraw rewards for several tokens owned by the sender in one transfer.
+    /// Tokens with nothing to claim are skipped.
+    BatchWithdrawTokenRewards {
+        token_ids: Vec<String>,
+    },
+
     UpdateMinter {
         minter: String,
     },
@@ -158,6 +398,33 @@ pub enum QueryMsg<Q: JsonSchema> {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Number of tokens held by an address
+    #[returns(BalanceResponse)]
+    Balance { owner: String },
+    /// Number of addresses holding at least one token
+    #[returns(NumOwnersResponse)]
+    NumOwners {},
+    /// Lists holders with their number of tokens, ordered by address
+    #[returns(OwnersResponse)]
+    AllOwners {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the tokens having an attribute, ordered by token id
+    #[returns(cw721::TokensResponse)]
+    TokensByTrait {
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns how many tokens have each attribute, for rarity, ordered by
+    /// `(trait_type, value)`
+    #[returns(TraitCountsResponse)]
+    TraitCounts {
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+    },
 
     /// Return the minter
     #[returns(MinterResponse)]
@@ -166,6 +433,122 @@ pub enum QueryMsg<Q: JsonSchema> {
     #[returns(TotalRewardResponse)]
     TotalArchReward { token_id: Option<String> },
 
+    /// Return the maximum number of tokens that can be minted
+    #[returns(MaxSupplyResponse)]
+    MaxSupply {},
+
+    /// Return whether the collection, or a single token if given, can change hands
+    #[returns(TransferableResponse)]
+    Transferable { token_id: Option<String> },
+
+    /// Return whether metadata of the collection, or of a single token if given, is frozen
+    #[returns(MetadataFrozenResponse)]
+    MetadataFrozen { token_id: Option<String> },
+
+    /// Return the collection default royalty, if any
+    #[returns(Option<crate::state::RoyaltyInfo>)]
+    DefaultRoyalty {},
+
+    /// Return whether royalties are shared with holders
+    #[returns(RoyaltyModeResponse)]
+    RoyaltyMode {},
+
+    /// Return the listing of a token, if any
+    #[returns(Option<crate::state::Listing>)]
+    Listing { token_id: String },
+
+    /// List all listings, ordered by token id
+    #[returns(ListingsResponse)]
+    Listings {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// List the listings of a seller, ordered by token id
+    #[returns(ListingsResponse)]
+    ListingsBySeller {
+        seller: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// List all listings from the cheapest. `start_after` is the (price, token id)
+    /// of the last listing of the previous page.
+    #[returns(ListingsResponse)]
+    ListingsByPrice {
+        start_after: Option<(Uint128, String)>,
+        limit: Option<u32>,
+    },
+
+    /// List the offers on a token, ordered by bidder
+    #[returns(OffersResponse)]
+    Offers {
+        token_id: String,
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Return the auction of a token, if any
+    #[returns(Option<crate::state::Auction>)]
+    Auction { token_id: String },
+
+    /// Return the highest royalty tokens can set
+    #[returns(MaxRoyaltyResponse)]
+    MaxRoyalty {},
+
+    /// Return the delayed reveal settings, if any
+    #[returns(Option<crate::state::RevealConfig>)]
+    RevealConfig {},
+
+    /// Return the base uri of tokens without an explicit token uri
+    #[returns(BaseTokenUriResponse)]
+    BaseTokenUri {},
+
+    /// Return the public sale settings, if any
+    #[returns(Option<crate::state::MintConfig>)]
+    MintConfig {},
+
+    /// Return how many tokens an address minted in the public sale
+    #[returns(MintCountResponse)]
+    MintCount { address: String },
+
+    /// List all allowlist phases
+    #[returns(MintPhasesResponse)]
+    MintPhases {},
+
+    /// Return whether the address can mint in the active allowlist phase with the given
+    /// allocation and proof
+    #[returns(WhitelistEligibilityResponse)]
+    WhitelistEligibility {
+        address: String,
+        allocation: u32,
+        proof: Vec<HexBinary>,
+    },
+
+    /// Return the reason an address is blocked, if it is
+    #[returns(Option<crate::state::BlockReason>)]
+    BlockStatus { address: String },
+
+    /// List blocked addresses
+    #[returns(BlocklistResponse)]
+    Blocklist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Return which groups of handlers are paused
+    #[returns(crate::state::PauseConfig)]
+    PauseStatus {},
+
+    /// Return the restrictions on `WithdrawRewards`
+    #[returns(crate::state::WithdrawConfig)]
+    WithdrawConfig {},
+
+    /// Compares the contract's bank balance against the rewards still owed to token owners
+    #[returns(SolvencyResponse)]
+    Solvency {},
+
     /// Extension query
     #[returns(())]
     Extension { msg: Q },
@@ -174,7 +557,150 @@ pub enum QueryMsg<Q: JsonSchema> {
 #[cw_serde]
 pub struct TotalRewardResponse {
     pub total_arch_reward: u128,
+    /// Whether a withdrawal would currently pay out anything
+    pub claimable: bool,
+}
+
+#[cw_serde]
+pub struct MaxSupplyResponse {
+    pub max_supply: Option<u64>,
 }
+
+#[cw_serde]
+pub struct BlockedAddress {
+    pub address: String,
+    pub reason: BlockReason,
+}
+
+#[cw_serde]
+pub struct BlocklistResponse {
+    pub blocked: Vec<BlockedAddress>,
+}
+
+#[cw_serde]
+pub struct TransferableResponse {
+    pub transferable: bool,
+}
+
+#[cw_serde]
+pub struct ListingInfo {
+    pub token_id: String,
+    pub seller: String,
+    pub price: Coin,
+}
+
+#[cw_serde]
+pub struct ListingsResponse {
+    pub listings: Vec<ListingInfo>,
+}
+
+#[cw_serde]
+pub struct BalanceResponse {
+    pub balance: u64,
+}
+
+#[cw_serde]
+pub struct NumOwnersResponse {
+    pub count: u64,
+}
+
+#[cw_serde]
+pub struct OwnerBalance {
+    pub owner: String,
+    pub balance: u64,
+}
+
+#[cw_serde]
+pub struct OwnersResponse {
+    pub owners: Vec<OwnerBalance>,
+}
+
+#[cw_serde]
+pub struct TraitCount {
+    pub trait_type: String,
+    pub value: String,
+    pub count: u64,
+}
+
+#[cw_serde]
+pub struct TraitCountsResponse {
+    pub traits: Vec<TraitCount>,
+}
+
+#[cw_serde]
+pub struct OfferInfo {
+    pub bidder: String,
+    pub amount: Coin,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct OffersResponse {
+    pub offers: Vec<OfferInfo>,
+}
+
+#[cw_serde]
+pub struct RoyaltyModeResponse {
+    pub holders: bool,
+}
+
+#[cw_serde]
+pub struct MaxRoyaltyResponse {
+    pub max_royalty_bps: u64,
+}
+
+#[cw_serde]
+pub struct MetadataFrozenResponse {
+    pub frozen: bool,
+}
+
+#[cw_serde]
+pub struct BaseTokenUriResponse {
+    pub base_token_uri: Option<String>,
+    pub frozen: bool,
+}
+
+#[cw_serde]
+pub struct MintPhaseInfo {
+    pub phase_id: u32,
+    pub phase: MintPhase,
+}
+
+#[cw_serde]
+pub struct MintPhasesResponse {
+    pub phases: Vec<MintPhaseInfo>,
+}
+
+#[cw_serde]
+pub struct WhitelistEligibilityResponse {
+    /// The active phase, if any
+    pub phase_id: Option<u32>,
+    pub eligible: bool,
+    /// Number of mints the address has left from its allocation in the active phase
+    pub remaining: u32,
+}
+
+#[cw_serde]
+pub struct MintCountResponse {
+    pub count: u32,
+}
+
+#[cw_serde]
+pub struct DenomSolvency {
+    pub denom: String,
+    /// Bank balance held by the contract
+    pub balance: Uint128,
+    /// Rewards credited to tokens but not yet claimed, plus funds escrowed for offers and bids
+    pub owed: Uint128,
+    pub surplus: Uint128,
+    pub deficit: Uint128,
+}
+
+#[cw_serde]
+pub struct SolvencyResponse {
+    pub denoms: Vec<DenomSolvency>,
+}
+
 /// Shows who can mint these tokens
 #[cw_serde]
 pub struct MinterResponse {
@@ -191,6 +717,12 @@ pub enum Cw2981QueryMsg {
     },
     #[returns(CheckRoyaltiesResponse)]
     CheckRoyalties {},
+    /// Breaks the royalty of a sale down per recipient
+    #[returns(RoyaltySplitsResponse)]
+    RoyaltySplits {
+        token_id: String,
+        sale_price: Uint128,
+    },
 }
 
 impl Default for Cw2981QueryMsg {
@@ -207,6 +739,17 @@ pub struct RoyaltiesInfoResponse {
     pub royalty_amount: Uint128,
 }
 
+#[cw_serde]
+pub struct RoyaltySplitAmount {
+    pub address: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct RoyaltySplitsResponse {
+    pub splits: Vec<RoyaltySplitAmount>,
+}
+
 #[cw_serde]
 pub struct CheckRoyaltiesResponse {
     pub royalty_payments: bool,
@@ -2,8 +2,8 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use cosmwasm_std::{
-    to_binary, Addr, Binary, BlockInfo, CustomMsg, Decimal, Deps, Empty, Env, Order, StdError,
-    StdResult, Uint128,
+    to_binary, Addr, Binary, BlockInfo, CustomMsg, Decimal, Deps, Empty, Env, HexBinary, Order,
+    StdError, StdResult, Uint128,
 };
 
 use cw721::{
@@ -15,10 +15,19 @@ use cw_storage_plus::Bound;
 use cw_utils::maybe_addr;
 
 use crate::msg::{
-    CheckRoyaltiesResponse, Cw2981QueryMsg, MinterResponse, QueryMsg, RoyaltiesInfoResponse,
-    TotalRewardResponse,
+    BalanceResponse, BaseTokenUriResponse, BlockedAddress, BlocklistResponse,
+    CheckRoyaltiesResponse, Cw2981QueryMsg, DenomSolvency, ListingInfo, ListingsResponse,
+    MaxRoyaltyResponse, MaxSupplyResponse, MetadataFrozenResponse, MintCountResponse,
+    MintPhaseInfo, MintPhasesResponse, MinterResponse, NumOwnersResponse, OfferInfo,
+    OffersResponse, OwnerBalance, OwnersResponse, QueryMsg, RoyaltiesInfoResponse,
+    RoyaltyModeResponse, RoyaltySplitAmount, RoyaltySplitsResponse, SolvencyResponse,
+    TotalRewardResponse, TraitCount, TraitCountsResponse, TransferableResponse,
+    WhitelistEligibilityResponse,
+};
+use crate::state::{
+    Approval, BlockReason, Cw721Contract, Listing, MintConfig, PauseConfig, TokenInfo,
+    WithdrawConfig, MAX_ROYALTY_BPS,
 };
-use crate::state::{Approval, Cw721Contract, TokenInfo};
 use crate::Extension;
 
 const DEFAULT_LIMIT: u32 = 10;
@@ -43,7 +52,7 @@ where
     fn nft_info(&self, deps: Deps, token_id: String) -> StdResult<NftInfoResponse<T>> {
         let info = self.tokens.load(deps.storage, &token_id)?;
         Ok(NftInfoResponse {
-            token_uri: info.token_uri,
+            token_uri: self.resolve_token_uri(deps.storage, &token_id, info.token_uri)?,
             extension: info.extension,
         })
     }
@@ -239,7 +248,7 @@ where
                 approvals: humanize_approvals(&env.block, &info, include_expired),
             },
             info: NftInfoResponse {
-                token_uri: info.token_uri,
+                token_uri: self.resolve_token_uri(deps.storage, &token_id, info.token_uri)?,
                 extension: info.extension,
             },
         })
@@ -306,6 +315,24 @@ where
             QueryMsg::AllTokens { start_after, limit } => {
                 to_binary(&self.all_tokens(deps, start_after, limit)?)
             }
+            QueryMsg::Balance { owner } => to_binary(&BalanceResponse {
+                balance: self.balance(deps.storage, &deps.api.addr_validate(&owner)?)?,
+            }),
+            QueryMsg::NumOwners {} => to_binary(&NumOwnersResponse {
+                count: self.num_owners(deps.storage)?,
+            }),
+            QueryMsg::AllOwners { start_after, limit } => {
+                to_binary(&self.all_owners(deps, start_after, limit)?)
+            }
+            QueryMsg::TokensByTrait {
+                trait_type,
+                value,
+                start_after,
+                limit,
+            } => to_binary(&self.tokens_by_trait(deps, trait_type, value, start_after, limit)?),
+            QueryMsg::TraitCounts { start_after, limit } => {
+                to_binary(&self.trait_counts(deps, start_after, limit)?)
+            }
             QueryMsg::Approval {
                 token_id,
                 spender,
@@ -327,12 +354,80 @@ where
             QueryMsg::TotalArchReward { token_id } => {
                 to_binary(&self.get_total_arch_rewards(deps, token_id)?)
             }
+            QueryMsg::MaxSupply {} => to_binary(&self.query_max_supply(deps)?),
+            QueryMsg::Transferable { token_id } => {
+                to_binary(&self.query_transferable(deps, token_id)?)
+            }
+            QueryMsg::MetadataFrozen { token_id } => {
+                to_binary(&self.query_metadata_frozen(deps, token_id)?)
+            }
+            QueryMsg::DefaultRoyalty {} => to_binary(&self.default_royalty.may_load(deps.storage)?),
+            QueryMsg::Listing { token_id } => {
+                to_binary(&self.listings.may_load(deps.storage, &token_id)?)
+            }
+            QueryMsg::Listings { start_after, limit } => {
+                to_binary(&self.all_listings(deps, start_after, limit)?)
+            }
+            QueryMsg::ListingsBySeller {
+                seller,
+                start_after,
+                limit,
+            } => to_binary(&self.listings_by_seller(deps, seller, start_after, limit)?),
+            QueryMsg::ListingsByPrice { start_after, limit } => {
+                to_binary(&self.listings_by_price(deps, start_after, limit)?)
+            }
+            QueryMsg::Offers {
+                token_id,
+                include_expired,
+                start_after,
+                limit,
+            } => to_binary(&self.offers(
+                deps,
+                env,
+                token_id,
+                include_expired.unwrap_or(false),
+                start_after,
+                limit,
+            )?),
+            QueryMsg::Auction { token_id } => {
+                to_binary(&self.auctions.may_load(deps.storage, &token_id)?)
+            }
+            QueryMsg::RoyaltyMode {} => to_binary(&RoyaltyModeResponse {
+                holders: self
+                    .holder_royalties
+                    .may_load(deps.storage)?
+                    .unwrap_or_default(),
+            }),
+            QueryMsg::MaxRoyalty {} => to_binary(&MaxRoyaltyResponse {
+                max_royalty_bps: self.max_royalty(deps.storage)?,
+            }),
+            QueryMsg::RevealConfig {} => to_binary(&self.reveal.may_load(deps.storage)?),
+            QueryMsg::BaseTokenUri {} => to_binary(&self.query_base_token_uri(deps)?),
+            QueryMsg::MintConfig {} => to_binary(&self.query_mint_config(deps)?),
+            QueryMsg::MintCount { address } => to_binary(&self.mint_count(deps, address)?),
+            QueryMsg::MintPhases {} => to_binary(&self.mint_phases(deps)?),
+            QueryMsg::WhitelistEligibility {
+                address,
+                allocation,
+                proof,
+            } => to_binary(&self.whitelist_eligibility(deps, env, address, allocation, proof)?),
+            QueryMsg::BlockStatus { address } => to_binary(&self.block_status(deps, address)?),
+            QueryMsg::Blocklist { start_after, limit } => {
+                to_binary(&self.blocklist(deps, start_after, limit)?)
+            }
+            QueryMsg::PauseStatus {} => to_binary(&self.pause_status(deps)?),
+            QueryMsg::WithdrawConfig {} => to_binary(&self.query_withdraw_config(deps)?),
+            QueryMsg::Solvency {} => to_binary(&self.solvency(deps, env)?),
             QueryMsg::Extension { msg } => match msg {
                 Cw2981QueryMsg::RoyaltyInfo {
                     token_id,
                     sale_price,
-                } => to_binary(&self.query_royalties_info(deps, token_id, sale_price)?),
+                } => to_binary(&self.query_royalties_info(deps, env, token_id, sale_price)?),
                 Cw2981QueryMsg::CheckRoyalties {} => to_binary(&self.check_royalties(deps)?),
+                Cw2981QueryMsg::RoyaltySplits {
+                    token_id,
+                    sale_price,
+                } => to_binary(&self.query_royalty_splits(deps, env, token_id, sale_price)?),
             },
         }
     }
@@ -354,66 +449,469 @@ where
         deps: Deps,
         token_id: Option<String>,
     ) -> StdResult<TotalRewardResponse> {
-        let total_all = self
-            .total_arch_reward
-            .may_load(deps.storage)
-            .unwrap_or_default()
-            .unwrap();
-
-        return if let Some(token_id) = token_id {
+        if let Some(token_id) = token_id {
             let info = self.tokens.load(deps.storage, &token_id)?;
-
-            let total_supply = self.token_count.load(deps.storage)?;
+            let available = self
+                .token_rewards(deps.storage, info.reward_claimed)
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
 
             Ok(TotalRewardResponse {
-                total_arch_reward: total_all / total_supply as u128 - info.reward_claimed,
+                total_arch_reward: available.u128(),
+                claimable: !available.is_zero(),
             })
         } else {
             Ok(TotalRewardResponse {
-                total_arch_reward: total_all,
+                total_arch_reward: self
+                    .total_arch_reward
+                    .may_load(deps.storage)?
+                    .unwrap_or_default(),
+                claimable: self.outstanding_rewards(deps.storage)? > 0,
             })
+        }
+    }
+
+    pub fn query_max_supply(&self, deps: Deps) -> StdResult<MaxSupplyResponse> {
+        Ok(MaxSupplyResponse {
+            max_supply: self.max_supply.may_load(deps.storage)?,
+        })
+    }
+
+    pub fn query_transferable(
+        &self,
+        deps: Deps,
+        token_id: Option<String>,
+    ) -> StdResult<TransferableResponse> {
+        Ok(TransferableResponse {
+            transferable: self.is_transferable(deps.storage, token_id.as_deref())?,
+        })
+    }
+
+    pub fn query_metadata_frozen(
+        &self,
+        deps: Deps,
+        token_id: Option<String>,
+    ) -> StdResult<MetadataFrozenResponse> {
+        Ok(MetadataFrozenResponse {
+            frozen: self.is_metadata_frozen(deps.storage, token_id.as_deref())?,
+        })
+    }
+
+    pub fn query_base_token_uri(&self, deps: Deps) -> StdResult<BaseTokenUriResponse> {
+        Ok(BaseTokenUriResponse {
+            base_token_uri: self.base_token_uri.may_load(deps.storage)?,
+            frozen: self
+                .base_token_uri_frozen
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        })
+    }
+
+    pub fn query_mint_config(&self, deps: Deps) -> StdResult<Option<MintConfig>> {
+        self.mint_config.may_load(deps.storage)
+    }
+
+    pub fn mint_count(&self, deps: Deps, address: String) -> StdResult<MintCountResponse> {
+        let address = deps.api.addr_validate(&address)?;
+        let count = self
+            .address_mint_count
+            .may_load(deps.storage, &address)?
+            .unwrap_or_default();
+        Ok(MintCountResponse { count })
+    }
+
+    pub fn mint_phases(&self, deps: Deps) -> StdResult<MintPhasesResponse> {
+        let phases = self
+            .mint_phases
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(phase_id, phase)| MintPhaseInfo { phase_id, phase }))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(MintPhasesResponse { phases })
+    }
+
+    pub fn whitelist_eligibility(
+        &self,
+        deps: Deps,
+        env: Env,
+        address: String,
+        allocation: u32,
+        proof: Vec<HexBinary>,
+    ) -> StdResult<WhitelistEligibilityResponse> {
+        let address = deps.api.addr_validate(&address)?;
+        let (phase_id, phase) = match self.active_mint_phase(deps.storage, &env.block)? {
+            Some(active) => active,
+            None => {
+                return Ok(WhitelistEligibilityResponse {
+                    phase_id: None,
+                    eligible: false,
+                    remaining: 0,
+                })
+            }
         };
+
+        let remaining = if phase.verify(&address, allocation, &proof) {
+            let minted = self
+                .phase_mint_count
+                .may_load(deps.storage, (phase_id, &address))?
+                .unwrap_or_default();
+            allocation.saturating_sub(minted)
+        } else {
+            0
+        };
+
+        Ok(WhitelistEligibilityResponse {
+            phase_id: Some(phase_id),
+            eligible: remaining > 0,
+            remaining,
+        })
+    }
+
+    pub fn all_listings(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ListingsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let listings = self
+            .listings
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(parse_listing)
+            .collect::<StdResult<_>>()?;
+        Ok(ListingsResponse { listings })
+    }
+
+    pub fn listings_by_seller(
+        &self,
+        deps: Deps,
+        seller: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ListingsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let seller_addr = deps.api.addr_validate(&seller)?;
+        let listings = self
+            .listings
+            .idx
+            .seller
+            .prefix(seller_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(parse_listing)
+            .collect::<StdResult<_>>()?;
+        Ok(ListingsResponse { listings })
+    }
+
+    pub fn listings_by_price(
+        &self,
+        deps: Deps,
+        start_after: Option<(Uint128, String)>,
+        limit: Option<u32>,
+    ) -> StdResult<ListingsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|(price, token_id)| Bound::exclusive((price.u128(), token_id)));
+
+        let listings = self
+            .listings
+            .idx
+            .price
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(parse_listing)
+            .collect::<StdResult<_>>()?;
+        Ok(ListingsResponse { listings })
+    }
+
+    pub fn all_owners(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<OwnersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let owners = self
+            .balances
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(owner, balance)| OwnerBalance {
+                    owner: owner.into_string(),
+                    balance,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(OwnersResponse { owners })
+    }
+
+    pub fn tokens_by_trait(
+        &self,
+        deps: Deps,
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let tokens = self
+            .trait_tokens
+            .prefix((&trait_type, &value))
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(TokensResponse { tokens })
     }
+
+    pub fn trait_counts(
+        &self,
+        deps: Deps,
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+    ) -> StdResult<TraitCountsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after
+            .as_ref()
+            .map(|(trait_type, value)| Bound::exclusive((trait_type.as_str(), value.as_str())));
+
+        let traits = self
+            .trait_counts
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|((trait_type, value), count)| TraitCount {
+                    trait_type,
+                    value,
+                    count,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(TraitCountsResponse { traits })
+    }
+
+    pub fn offers(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<OffersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let offers = self
+            .offers
+            .prefix(&token_id)
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|item| match item {
+                Ok((_, offer)) => include_expired || !offer.expires.is_expired(&env.block),
+                Err(_) => true,
+            })
+            .take(limit)
+            .map(|item| {
+                item.map(|(bidder, offer)| OfferInfo {
+                    bidder: bidder.into_string(),
+                    amount: offer.amount,
+                    expires: offer.expires,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(OffersResponse { offers })
+    }
+
+    pub fn block_status(&self, deps: Deps, address: String) -> StdResult<Option<BlockReason>> {
+        let address = deps.api.addr_validate(&address)?;
+        self.blocklist.may_load(deps.storage, &address)
+    }
+
+    pub fn blocklist(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<BlocklistResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let blocked = self
+            .blocklist
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(address, reason)| BlockedAddress {
+                    address: address.to_string(),
+                    reason,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(BlocklistResponse { blocked })
+    }
+
+    pub fn pause_status(&self, deps: Deps) -> StdResult<PauseConfig> {
+        Ok(self.pause.may_load(deps.storage)?.unwrap_or_default())
+    }
+
+    pub fn query_withdraw_config(&self, deps: Deps) -> StdResult<WithdrawConfig> {
+        Ok(self
+            .withdraw_config
+            .may_load(deps.storage)?
+            .unwrap_or_default())
+    }
+
+    pub fn solvency(&self, deps: Deps, env: Env) -> StdResult<SolvencyResponse> {
+        let denom = self.rewards_denom.load(deps.storage)?;
+        let balance = deps
+            .querier
+            .query_balance(&env.contract.address, &denom)?
+            .amount;
+        let escrowed = self.escrow.may_load(deps.storage)?.unwrap_or_default();
+        let owed = Uint128::new(self.outstanding_rewards(deps.storage)?) + escrowed;
+
+        Ok(SolvencyResponse {
+            denoms: vec![DenomSolvency {
+                denom,
+                balance,
+                owed,
+                surplus: balance.saturating_sub(owed),
+                deficit: owed.saturating_sub(balance),
+            }],
+        })
+    }
+
     pub fn query_royalties_info(
         &self,
         deps: Deps,
+        env: Env,
         token_id: String,
         sale_price: Uint128,
     ) -> StdResult<RoyaltiesInfoResponse> {
         let contract = Cw721Contract::<Extension, Empty, Empty, Cw2981QueryMsg>::default();
 
         let token_info: TokenInfo<Extension> = contract.tokens.load(deps.storage, &token_id)?;
-
-        let royalty_percentage = match token_info.extension {
-            Some(ref ext) => match ext.royalty_percentage {
-                Some(percentage) => Decimal::percent(percentage),
-                None => Decimal::percent(0),
+        let default_royalty = self.default_royalty.may_load(deps.storage)?;
+
+        // a token's own royalty overrides the collection default
+        let token_royalty = token_info.extension.and_then(|ext| {
+            ext.royalty_bps()
+                .map(|bps| (bps, ext.royalty_payment_address))
+        });
+        let (royalty_share, royalty_address) = match token_royalty {
+            Some((bps, royalty_payment_address)) => (
+                Decimal::from_ratio(bps, MAX_ROYALTY_BPS),
+                royalty_payment_address
+                    .or_else(|| default_royalty.map(|royalty| royalty.payment_address.into())),
+            ),
+            None => match default_royalty {
+                Some(royalty) => (royalty.share, Some(royalty.payment_address.into())),
+                None => (Decimal::zero(), None),
             },
-            None => Decimal::percent(0),
         };
-        let royalty_from_sale_price = sale_price * royalty_percentage;
+        // the same share is owed, but to the holders through this contract
+        let royalty_address = if self
+            .holder_royalties
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+        {
+            Some(env.contract.address.into_string())
+        } else {
+            royalty_address
+        };
 
-        let royalty_address = match token_info.extension {
-            Some(ext) => match ext.royalty_payment_address {
-                Some(addr) => addr,
-                None => String::from(""),
-            },
-            None => String::from(""),
+        // nothing is owed when there is nobody to pay
+        match royalty_address {
+            Some(address) => Ok(RoyaltiesInfoResponse {
+                address,
+                // rounds down, the seller never pays more than the royalty
+                royalty_amount: sale_price * royalty_share,
+            }),
+            None => Ok(RoyaltiesInfoResponse {
+                address: String::from(""),
+                royalty_amount: Uint128::zero(),
+            }),
+        }
+    }
+
+    pub fn query_royalty_splits(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        sale_price: Uint128,
+    ) -> StdResult<RoyaltySplitsResponse> {
+        let contract = Cw721Contract::<Extension, Empty, Empty, Cw2981QueryMsg>::default();
+
+        let token_info: TokenInfo<Extension> = contract.tokens.load(deps.storage, &token_id)?;
+        let royalty = self.query_royalties_info(deps, env, token_id, sale_price)?;
+        let token_splits = if self
+            .holder_royalties
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+        {
+            None
+        } else {
+            token_info.extension.and_then(|ext| ext.royalty_splits)
         };
 
-        Ok(RoyaltiesInfoResponse {
-            address: royalty_address,
-            royalty_amount: royalty_from_sale_price,
-        })
+        let splits = match token_splits {
+            Some(splits) => {
+                let mut splits: Vec<_> = splits
+                    .into_iter()
+                    .map(|split| RoyaltySplitAmount {
+                        address: split.address,
+                        amount: sale_price.multiply_ratio(split.share_bps, MAX_ROYALTY_BPS),
+                    })
+                    .collect();
+                // rounding dust goes to the first recipient so the splits add up to the royalty
+                let split_total: Uint128 = splits.iter().map(|split| split.amount).sum();
+                if let Some(first) = splits.first_mut() {
+                    first.amount += royalty.royalty_amount.saturating_sub(split_total);
+                }
+                splits
+            }
+            None if royalty.address.is_empty() => vec![],
+            None => vec![RoyaltySplitAmount {
+                address: royalty.address,
+                amount: royalty.royalty_amount,
+            }],
+        };
+        Ok(RoyaltySplitsResponse { splits })
     }
 
-    pub fn check_royalties(&self, _deps: Deps) -> StdResult<CheckRoyaltiesResponse> {
+    /// Royalties are due if the collection has a default or any token sets its own
+    pub fn check_royalties(&self, deps: Deps) -> StdResult<CheckRoyaltiesResponse> {
+        let default_royalty = self
+            .default_royalty
+            .may_load(deps.storage)?
+            .map_or(false, |royalty| !royalty.share.is_zero());
+        let royalty_tokens = self
+            .royalty_token_count
+            .may_load(deps.storage)?
+            .unwrap_or_default();
         Ok(CheckRoyaltiesResponse {
-            royalty_payments: true,
+            royalty_payments: default_royalty || royalty_tokens > 0,
         })
     }
 }
 
+fn parse_listing(item: StdResult<(String, Listing)>) -> StdResult<ListingInfo> {
+    item.map(|(token_id, listing)| ListingInfo {
+        token_id,
+        seller: listing.seller.into_string(),
+        price: listing.price,
+    })
+}
+
 fn parse_approval(item: StdResult<(Addr, Expiration)>) -> StdResult<cw721::Approval> {
     item.map(|(spender, expires)| cw721::Approval {
         spender: spender.to_string(),
@@ -1,12 +1,20 @@
+use cosmwasm_schema::cw_serde;
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
-use cosmwasm_std::{Addr, BlockInfo, CustomMsg, StdResult, Storage};
+use cosmwasm_std::{
+    from_slice, to_vec, Addr, BlockInfo, Coin, CustomMsg, Decimal, Empty, HexBinary, Order,
+    StdResult, Storage, Timestamp, Uint128, Uint64,
+};
 
 use cw721::{ContractInfoResponse, Expiration};
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_utils::Duration;
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
 
 pub struct Cw721Contract<'a, T, C, E, Q>
 where
@@ -19,9 +27,67 @@ where
     /// Stored as (granter, operator) giving operator full control over granter's account
     pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
     pub tokens: IndexedMap<'a, &'a str, TokenInfo<T>, TokenIndexes<'a, T>>,
+    /// Tokens by each `(trait_type, value)` of their metadata `attributes`
+    pub trait_tokens: Map<'a, (&'a str, &'a str, &'a str), Empty>,
+    /// Number of tokens sharing each `(trait_type, value)`
+    pub trait_counts: Map<'a, (&'a str, &'a str), u64>,
     pub total_arch_reward: Item<'a, u128>,
+    /// Sum of all rewards already paid out to token owners
+    pub total_rewards_claimed: Item<'a, u128>,
     pub minter: Item<'a, Addr>,
+    pub max_supply: Item<'a, u64>,
     pub rewards_denom: Item<'a, String>,
+    pub withdraw_config: Item<'a, WithdrawConfig>,
+    /// Block at which `WithdrawRewards` was last triggered
+    pub last_rewards_withdraw: Item<'a, BlockInfo>,
+    /// Sender of the in-flight `WithdrawRewards`, read back in the reply to pay the bounty
+    pub rewards_withdraw_caller: Item<'a, Addr>,
+    pub base_token_uri: Item<'a, String>,
+    pub base_token_uri_frozen: Item<'a, bool>,
+    /// Last id handed out to a token minted without an explicit id, never reused
+    pub token_id_counter: Item<'a, u64>,
+    pub mint_config: Item<'a, MintConfig>,
+    /// Number of tokens each address minted through `PublicMint`
+    pub address_mint_count: Map<'a, &'a Addr, u32>,
+    pub mint_phases: Map<'a, u32, MintPhase>,
+    /// Number of tokens each address minted through `WhitelistMint`, per phase
+    pub phase_mint_count: Map<'a, (u32, &'a Addr), u32>,
+    /// Whether tokens can change hands at all, true if unset
+    pub transferable: Item<'a, bool>,
+    /// Tokens the owner has made non-transferable individually
+    pub locked_tokens: Map<'a, &'a str, bool>,
+    pub pause: Item<'a, PauseConfig>,
+    /// Addresses that can't send, receive, mint or claim
+    pub blocklist: Map<'a, &'a Addr, BlockReason>,
+    /// Once set, no token's metadata can be updated
+    pub metadata_frozen: Item<'a, bool>,
+    /// Tokens whose metadata can no longer be updated
+    pub frozen_token_metadata: Map<'a, &'a str, bool>,
+    pub reveal: Item<'a, RevealConfig>,
+    /// Tokens reporting their stored uri whatever the reveal config: frozen tokens and tokens
+    /// updated after the reveal
+    pub pinned_token_uris: Map<'a, &'a str, bool>,
+    /// Royalty of tokens that don't set their own
+    pub default_royalty: Item<'a, RoyaltyInfo>,
+    /// Highest royalty tokens can set, in basis points
+    pub max_royalty_bps: Item<'a, u64>,
+    /// Whether royalties are paid to this contract and shared with holders
+    pub holder_royalties: Item<'a, bool>,
+    /// Number of tokens that set their own non-zero royalty
+    pub royalty_token_count: Item<'a, u64>,
+    /// Fixed-price offers to sell, the token stays with the seller until bought
+    pub listings: IndexedMap<'a, &'a str, Listing, ListingIndexes<'a>>,
+    /// Escrowed offers, by (token id, bidder)
+    pub offers: Map<'a, (&'a str, &'a Addr), Offer>,
+    /// Running auctions, the token is held by the contract until settled
+    pub auctions: Map<'a, &'a str, Auction>,
+    /// Total rewards denom held for open offers and auction bids, not available for rewards
+    pub escrow: Item<'a, Uint128>,
+    /// Number of tokens each address holds, addresses without tokens are removed. Tokens
+    /// escrowed in an auction still count for their seller.
+    pub balances: Map<'a, &'a Addr, u64>,
+    /// Number of addresses holding at least one token
+    pub num_owners: Item<'a, u64>,
 
     pub(crate) _custom_extension: PhantomData<T>,
     pub(crate) _custom_response: PhantomData<C>,
@@ -52,9 +118,43 @@ where
             "operators",
             "tokens",
             "tokens__owner",
+            "tokens__traits",
+            "tokens__trait_counts",
             "total_arch_reward",
+            "total_rewards_claimed",
             "minter",
+            "max_supply",
             "rewards_denom",
+            "withdraw_config",
+            "last_rewards_withdraw",
+            "rewards_withdraw_caller",
+            "base_token_uri",
+            "base_token_uri_frozen",
+            "token_id_counter",
+            "mint_config",
+            "address_mint_count",
+            "mint_phases",
+            "phase_mint_count",
+            "transferable",
+            "locked_tokens",
+            "pause",
+            "blocklist",
+            "metadata_frozen",
+            "frozen_token_metadata",
+            "reveal",
+            "pinned_token_uris",
+            "default_royalty",
+            "max_royalty_bps",
+            "holder_royalties",
+            "royalty_token_count",
+            "listings",
+            "listings__seller",
+            "listings__price",
+            "offers",
+            "auctions",
+            "escrow",
+            "balances",
+            "num_owners",
         )
     }
 }
@@ -65,27 +165,98 @@ where
     E: CustomMsg,
     Q: CustomMsg,
 {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         contract_key: &'a str,
         token_count_key: &'a str,
         operator_key: &'a str,
         tokens_key: &'a str,
         tokens_owner_key: &'a str,
+        trait_tokens_key: &'a str,
+        trait_counts_key: &'a str,
         total_arch_reward_key: &'a str,
+        total_rewards_claimed_key: &'a str,
         minter: &'a str,
+        max_supply_key: &'a str,
         rewards_denom: &'a str,
+        withdraw_config_key: &'a str,
+        last_rewards_withdraw_key: &'a str,
+        rewards_withdraw_caller_key: &'a str,
+        base_token_uri_key: &'a str,
+        base_token_uri_frozen_key: &'a str,
+        token_id_counter_key: &'a str,
+        mint_config_key: &'a str,
+        address_mint_count_key: &'a str,
+        mint_phases_key: &'a str,
+        phase_mint_count_key: &'a str,
+        transferable_key: &'a str,
+        locked_tokens_key: &'a str,
+        pause_key: &'a str,
+        blocklist_key: &'a str,
+        metadata_frozen_key: &'a str,
+        frozen_token_metadata_key: &'a str,
+        reveal_key: &'a str,
+        pinned_token_uris_key: &'a str,
+        default_royalty_key: &'a str,
+        max_royalty_bps_key: &'a str,
+        holder_royalties_key: &'a str,
+        royalty_token_count_key: &'a str,
+        listings_key: &'a str,
+        listings_seller_key: &'a str,
+        listings_price_key: &'a str,
+        offers_key: &'a str,
+        auctions_key: &'a str,
+        escrow_key: &'a str,
+        balances_key: &'a str,
+        num_owners_key: &'a str,
     ) -> Self {
         let indexes = TokenIndexes {
             owner: MultiIndex::new(token_owner_idx, tokens_key, tokens_owner_key),
         };
+        let listing_indexes = ListingIndexes {
+            seller: MultiIndex::new(listing_seller_idx, listings_key, listings_seller_key),
+            price: MultiIndex::new(listing_price_idx, listings_key, listings_price_key),
+        };
         Self {
             contract_info: Item::new(contract_key),
             token_count: Item::new(token_count_key),
             operators: Map::new(operator_key),
             tokens: IndexedMap::new(tokens_key, indexes),
+            trait_tokens: Map::new(trait_tokens_key),
+            trait_counts: Map::new(trait_counts_key),
             total_arch_reward: Item::new(total_arch_reward_key),
+            total_rewards_claimed: Item::new(total_rewards_claimed_key),
             minter: Item::new(minter),
+            max_supply: Item::new(max_supply_key),
             rewards_denom: Item::new(rewards_denom),
+            withdraw_config: Item::new(withdraw_config_key),
+            last_rewards_withdraw: Item::new(last_rewards_withdraw_key),
+            rewards_withdraw_caller: Item::new(rewards_withdraw_caller_key),
+            base_token_uri: Item::new(base_token_uri_key),
+            base_token_uri_frozen: Item::new(base_token_uri_frozen_key),
+            token_id_counter: Item::new(token_id_counter_key),
+            mint_config: Item::new(mint_config_key),
+            address_mint_count: Map::new(address_mint_count_key),
+            mint_phases: Map::new(mint_phases_key),
+            phase_mint_count: Map::new(phase_mint_count_key),
+            transferable: Item::new(transferable_key),
+            locked_tokens: Map::new(locked_tokens_key),
+            pause: Item::new(pause_key),
+            blocklist: Map::new(blocklist_key),
+            metadata_frozen: Item::new(metadata_frozen_key),
+            frozen_token_metadata: Map::new(frozen_token_metadata_key),
+            reveal: Item::new(reveal_key),
+            pinned_token_uris: Map::new(pinned_token_uris_key),
+            default_royalty: Item::new(default_royalty_key),
+            max_royalty_bps: Item::new(max_royalty_bps_key),
+            holder_royalties: Item::new(holder_royalties_key),
+            royalty_token_count: Item::new(royalty_token_count_key),
+            listings: IndexedMap::new(listings_key, listing_indexes),
+            offers: Map::new(offers_key),
+            auctions: Map::new(auctions_key),
+            escrow: Item::new(escrow_key),
+            balances: Map::new(balances_key),
+            num_owners: Item::new(num_owners_key),
             _custom_extension: PhantomData,
             _custom_response: PhantomData,
             _custom_execute: PhantomData,
@@ -93,32 +264,348 @@ where
         }
     }
 
-    pub fn add_total_arch_reward(&self, storage: &mut dyn Storage, add: u128) -> StdResult<u128> {
-        let mut val = self
-            .total_arch_reward
-            .may_load(storage)?
-            .unwrap_or_default();
+    pub fn add_total_arch_reward(
+        &self,
+        storage: &mut dyn Storage,
+        add: u128,
+    ) -> Result<u128, ContractError> {
+        let val = Uint128::new(
+            self.total_arch_reward
+                .may_load(storage)?
+                .unwrap_or_default(),
+        )
+        .checked_add(Uint128::new(add))
+        .map_err(|_| ContractError::RewardOverflow {})?
+        .u128();
 
-        val += add;
         self.total_arch_reward.save(storage, &val)?;
         Ok(val)
     }
 
+    pub fn add_total_rewards_claimed(
+        &self,
+        storage: &mut dyn Storage,
+        add: u128,
+    ) -> Result<u128, ContractError> {
+        let val = Uint128::new(
+            self.total_rewards_claimed
+                .may_load(storage)?
+                .unwrap_or_default(),
+        )
+        .checked_add(Uint128::new(add))
+        .map_err(|_| ContractError::RewardOverflow {})?
+        .u128();
+
+        self.total_rewards_claimed.save(storage, &val)?;
+        Ok(val)
+    }
+
+    /// Rewards a token with the given claimed amount can still withdraw,
+    /// i.e. `total_rewards / token_count - reward_claimed`
+    pub fn token_rewards(
+        &self,
+        storage: &dyn Storage,
+        reward_claimed: u128,
+    ) -> Result<Uint128, ContractError> {
+        let total_all = Uint128::new(
+            self.total_arch_reward
+                .may_load(storage)?
+                .unwrap_or_default(),
+        );
+
+        let token_count = self.token_count(storage)?;
+        if token_count == 0 {
+            return Err(ContractError::NoTokensMinted {});
+        }
+
+        let share = total_all
+            .checked_div(Uint128::from(token_count))
+            .map_err(|_| ContractError::NoTokensMinted {})?;
+
+        // once supply grows the share can drop below what was already claimed
+        Ok(share.saturating_sub(Uint128::new(reward_claimed)))
+    }
+
+    /// Rewards credited to tokens that have not been claimed yet
+    pub fn outstanding_rewards(&self, storage: &dyn Storage) -> StdResult<u128> {
+        let total = self
+            .total_arch_reward
+            .may_load(storage)?
+            .unwrap_or_default();
+        let claimed = self
+            .total_rewards_claimed
+            .may_load(storage)?
+            .unwrap_or_default();
+        Ok(total.saturating_sub(claimed))
+    }
+
     pub fn token_count(&self, storage: &dyn Storage) -> StdResult<u64> {
         Ok(self.token_count.may_load(storage)?.unwrap_or_default())
     }
 
+    /// Fails if minting `additional` tokens would exceed the max supply
+    pub fn assert_supply(
+        &self,
+        storage: &dyn Storage,
+        additional: u64,
+    ) -> Result<(), ContractError> {
+        if let Some(max_supply) = self.max_supply.may_load(storage)? {
+            let supply = self.token_count(storage)?.saturating_add(additional);
+            if supply > max_supply {
+                return Err(ContractError::MaxSupplyExceeded {});
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the collection, and the given token if any, may change hands
+    pub fn is_transferable(
+        &self,
+        storage: &dyn Storage,
+        token_id: Option<&str>,
+    ) -> StdResult<bool> {
+        if !self.transferable.may_load(storage)?.unwrap_or(true) {
+            return Ok(false);
+        }
+        match token_id {
+            Some(token_id) => Ok(!self.locked_tokens.has(storage, token_id)),
+            None => Ok(true),
+        }
+    }
+
+    pub fn assert_transferable(
+        &self,
+        storage: &dyn Storage,
+        token_id: Option<&str>,
+    ) -> Result<(), ContractError> {
+        if !self.is_transferable(storage, token_id)? {
+            return Err(ContractError::NonTransferable {});
+        }
+        Ok(())
+    }
+
+    pub fn assert_not_blocked(
+        &self,
+        storage: &dyn Storage,
+        addr: &Addr,
+    ) -> Result<(), ContractError> {
+        if self.blocklist.has(storage, addr) {
+            return Err(ContractError::Blocked {
+                address: addr.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether the collection, or the given token if any, has its metadata frozen
+    pub fn is_metadata_frozen(
+        &self,
+        storage: &dyn Storage,
+        token_id: Option<&str>,
+    ) -> StdResult<bool> {
+        if self.metadata_frozen.may_load(storage)?.unwrap_or_default() {
+            return Ok(true);
+        }
+        match token_id {
+            Some(token_id) => Ok(self.frozen_token_metadata.has(storage, token_id)),
+            None => Ok(false),
+        }
+    }
+
+    /// Highest royalty in basis points, 100% unless the owner lowered it
+    pub fn max_royalty(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self
+            .max_royalty_bps
+            .may_load(storage)?
+            .unwrap_or(MAX_ROYALTY_BPS))
+    }
+
     pub fn increment_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
         let val = self.token_count(storage)? + 1;
         self.token_count.save(storage, &val)?;
         Ok(val)
     }
 
+    /// Assigns the next sequential token id, skipping ids already taken by explicit mints
+    pub fn next_token_id(&self, storage: &mut dyn Storage) -> StdResult<String> {
+        let mut id = self.token_id_counter.may_load(storage)?.unwrap_or_default();
+        loop {
+            id += 1;
+            if !self.tokens.has(storage, &id.to_string()) {
+                break;
+            }
+        }
+        self.token_id_counter.save(storage, &id)?;
+        Ok(id.to_string())
+    }
+
+    /// Falls back to `{base_token_uri}/{token_id}.json` for tokens without a token uri
+    pub fn resolve_token_uri(
+        &self,
+        storage: &dyn Storage,
+        token_id: &str,
+        token_uri: Option<String>,
+    ) -> StdResult<Option<String>> {
+        let base = match self.reveal.may_load(storage)? {
+            Some(reveal) if !self.pinned_token_uris.has(storage, token_id) => {
+                return Ok(Some(match reveal.revealed_base_uri {
+                    // the revealed base replaces any uri stored at mint, such as the placeholder
+                    Some(base) => token_uri_from_base(&base, token_id),
+                    // every token shows the placeholder until the reveal
+                    None => reveal.placeholder_uri,
+                }));
+            }
+            _ => self.base_token_uri.may_load(storage)?,
+        };
+        if token_uri.is_some() {
+            return Ok(token_uri);
+        }
+        Ok(base.map(|base| token_uri_from_base(&base, token_id)))
+    }
+
+    /// The first mint phase, by id, running at the given block
+    pub fn active_mint_phase(
+        &self,
+        storage: &dyn Storage,
+        block: &BlockInfo,
+    ) -> StdResult<Option<(u32, MintPhase)>> {
+        for item in self
+            .mint_phases
+            .range(storage, None, None, Order::Ascending)
+        {
+            let (phase_id, phase) = item?;
+            if phase.is_active(block) {
+                return Ok(Some((phase_id, phase)));
+            }
+        }
+        Ok(None)
+    }
+
     pub fn decrement_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
         let val = self.token_count(storage)? - 1;
         self.token_count.save(storage, &val)?;
         Ok(val)
     }
+
+    pub fn balance(&self, storage: &dyn Storage, owner: &Addr) -> StdResult<u64> {
+        Ok(self.balances.may_load(storage, owner)?.unwrap_or_default())
+    }
+
+    pub fn num_owners(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.num_owners.may_load(storage)?.unwrap_or_default())
+    }
+
+    pub fn increment_balance(&self, storage: &mut dyn Storage, owner: &Addr) -> StdResult<u64> {
+        let val = self.balance(storage, owner)? + 1;
+        if val == 1 {
+            let owners = self.num_owners(storage)? + 1;
+            self.num_owners.save(storage, &owners)?;
+        }
+        self.balances.save(storage, owner, &val)?;
+        Ok(val)
+    }
+
+    pub fn decrement_balance(&self, storage: &mut dyn Storage, owner: &Addr) -> StdResult<u64> {
+        let val = Uint64::new(self.balance(storage, owner)?)
+            .checked_sub(Uint64::one())?
+            .u64();
+        if val == 0 {
+            let owners = Uint64::new(self.num_owners(storage)?).checked_sub(Uint64::one())?;
+            self.num_owners.save(storage, &owners.u64())?;
+            self.balances.remove(storage, owner);
+        } else {
+            self.balances.save(storage, owner, &val)?;
+        }
+        Ok(val)
+    }
+
+    /// Counts the totals kept alongside `tokens` for a contract migrating from a version that
+    /// didn't keep them, reading the tokens a page at a time
+    pub fn backfill_token_totals(&self, storage: &mut dyn Storage) -> StdResult<()>
+    where
+        T: RoyaltyExtension,
+    {
+        let mut claimed = Uint128::zero();
+        let mut royalty_tokens = 0u64;
+        let mut start: Option<Bound<&str>> = None;
+        loop {
+            let page = self
+                .tokens
+                .range(storage, start.take(), None, Order::Ascending)
+                .take(BACKFILL_PAGE_SIZE)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (token_id, token) in &page {
+                self.add_token_traits(storage, token_id, &token.extension)?;
+                // a token escrowed in an auction still counts for its seller
+                let holder = match self.auctions.may_load(storage, token_id)? {
+                    Some(auction) => auction.seller,
+                    None => token.owner.clone(),
+                };
+                self.increment_balance(storage, &holder)?;
+                claimed = claimed.checked_add(Uint128::new(token.reward_claimed))?;
+                if token.extension.has_payable_royalty() {
+                    royalty_tokens += 1;
+                }
+            }
+            match page.last() {
+                Some((token_id, _)) if page.len() == BACKFILL_PAGE_SIZE => {
+                    start = Some(Bound::ExclusiveRaw(token_id.clone().into_bytes()));
+                }
+                _ => break,
+            }
+        }
+        self.total_rewards_claimed.save(storage, &claimed.u128())?;
+        self.royalty_token_count.save(storage, &royalty_tokens)
+    }
+
+    /// Lists a token under each of its traits, traits it is already listed under are skipped
+    pub fn add_token_traits(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        extension: &T,
+    ) -> StdResult<()> {
+        for (trait_type, value) in token_traits(extension) {
+            let key = (trait_type.as_str(), value.as_str(), token_id);
+            if self.trait_tokens.has(storage, key) {
+                continue;
+            }
+            self.trait_tokens.save(storage, key, &Empty {})?;
+            let key = (trait_type.as_str(), value.as_str());
+            let count = self
+                .trait_counts
+                .may_load(storage, key)?
+                .unwrap_or_default();
+            self.trait_counts.save(storage, key, &(count + 1))?;
+        }
+        Ok(())
+    }
+
+    pub fn remove_token_traits(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        extension: &T,
+    ) -> StdResult<()> {
+        for (trait_type, value) in token_traits(extension) {
+            let key = (trait_type.as_str(), value.as_str(), token_id);
+            if !self.trait_tokens.has(storage, key) {
+                continue;
+            }
+            self.trait_tokens.remove(storage, key);
+            let key = (trait_type.as_str(), value.as_str());
+            let count = self
+                .trait_counts
+                .may_load(storage, key)?
+                .unwrap_or_default();
+            if count > 1 {
+                self.trait_counts.save(storage, key, &(count - 1))?;
+            } else {
+                self.trait_counts.remove(storage, key);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -138,6 +625,150 @@ pub struct TokenInfo<T> {
     pub extension: T,
 }
 
+/// 100% in basis points
+pub const MAX_ROYALTY_BPS: u64 = 10_000;
+
+/// Tokens read at once while backfilling totals
+const BACKFILL_PAGE_SIZE: usize = 50;
+
+/// Collection-wide royalty, used for tokens that don't set their own
+#[cw_serde]
+pub struct RoyaltyInfo {
+    pub payment_address: Addr,
+    /// Fraction of the sale price, at most 1
+    pub share: Decimal,
+}
+
+/// Delayed reveal of the collection's token uris
+#[cw_serde]
+pub struct RevealConfig {
+    /// Token uri reported for every token until the reveal
+    pub placeholder_uri: String,
+    /// sha256 of the final base uri followed by the provenance preimage
+    pub provenance_hash: HexBinary,
+    /// Replaces the base token uri once revealed
+    pub revealed_base_uri: Option<String>,
+}
+
+impl RevealConfig {
+    /// Whether `base_uri` and `preimage` open the committed provenance hash
+    pub fn matches(&self, base_uri: &str, preimage: &str) -> bool {
+        let mut hasher = Sha256::new();
+        hasher.update(base_uri.as_bytes());
+        hasher.update(preimage.as_bytes());
+        self.provenance_hash.as_slice() == hasher.finalize().as_slice()
+    }
+}
+
+/// Groups of handlers the owner can stop in an emergency, nothing is paused by default
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseConfig {
+    /// All mint messages
+    pub minting: bool,
+    /// Transfers and sends, single and batched
+    pub transfers: bool,
+    /// Token owners withdrawing their share of the rewards
+    pub claims: bool,
+    /// `WithdrawRewards` pulling rewards from the Archway rewards module
+    pub withdrawals: bool,
+}
+
+/// Why and when the owner blocked an address
+#[cw_serde]
+pub struct BlockReason {
+    pub reason: String,
+    pub blocked_at: Timestamp,
+}
+
+/// Restrictions on who may trigger `WithdrawRewards` and how often.
+/// Every field is optional, the default leaves withdrawals open to anyone.
+#[cw_serde]
+#[derive(Default)]
+pub struct WithdrawConfig {
+    /// Minimum number of blocks or seconds between two withdrawals
+    pub min_interval: Option<Duration>,
+    /// Minimum amount of pending `rewards_denom` rewards before a withdrawal is allowed
+    pub min_pending_rewards: Option<Uint128>,
+    /// If set, only these addresses can trigger a withdrawal
+    pub keepers: Option<Vec<Addr>>,
+    /// Reward paid to whoever triggers a withdrawal
+    pub keeper_bounty: Option<KeeperBounty>,
+}
+
+#[cw_serde]
+pub struct KeeperBounty {
+    /// Share of the withdrawn rewards in basis points
+    pub bps: u64,
+    /// Maximum bounty paid for a single withdrawal
+    pub cap: Uint128,
+}
+
+impl KeeperBounty {
+    pub fn amount(&self, withdrawn: Uint128) -> Uint128 {
+        withdrawn.multiply_ratio(self.bps, 10_000u128).min(self.cap)
+    }
+}
+
+/// Settings for the public sale through `PublicMint`
+#[cw_serde]
+pub struct MintConfig {
+    pub price: Coin,
+    /// Maximum number of tokens a single address can mint
+    pub per_address_limit: Option<u32>,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    /// Receiver of the mint proceeds, if unset they are added to the reward pool
+    pub treasury: Option<Addr>,
+}
+
+pub fn token_uri_from_base(base: &str, token_id: &str) -> String {
+    format!("{}/{}.json", base.trim_end_matches('/'), token_id)
+}
+
+/// An allowlist sale phase for `WhitelistMint`
+#[cw_serde]
+pub struct MintPhase {
+    /// Root of a sha256 Merkle tree whose leaves are `sha256(address ++ allocation)`, with
+    /// the allocation as 4 big-endian bytes. Pairs are hashed in sorted order.
+    pub merkle_root: HexBinary,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub price: Coin,
+    /// Receiver of the mint proceeds, if unset they are added to the reward pool
+    pub treasury: Option<Addr>,
+}
+
+impl MintPhase {
+    pub fn is_active(&self, block: &BlockInfo) -> bool {
+        self.start_time <= block.time && block.time < self.end_time
+    }
+
+    /// Returns true if the proof shows `address` with `allocation` is a leaf of `merkle_root`
+    pub fn verify(&self, address: &Addr, allocation: u32, proof: &[HexBinary]) -> bool {
+        let mut hash = Sha256::new()
+            .chain_update(address.as_bytes())
+            .chain_update(allocation.to_be_bytes())
+            .finalize()
+            .to_vec();
+        for sibling in proof {
+            if sibling.len() != 32 {
+                return false;
+            }
+            let mut hasher = Sha256::new();
+            if hash.as_slice() <= sibling.as_slice() {
+                hasher.update(&hash);
+                hasher.update(sibling.as_slice());
+            } else {
+                hasher.update(sibling.as_slice());
+                hasher.update(&hash);
+            }
+            hash = hasher.finalize().to_vec();
+        }
+        self.merkle_root.as_slice() == hash.as_slice()
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Approval {
     /// Account that can transfer/send the token
@@ -172,3 +803,96 @@ where
 pub fn token_owner_idx<T>(_pk: &[u8], d: &TokenInfo<T>) -> Addr {
     d.owner.clone()
 }
+
+/// Token extensions that can set a royalty of their own
+pub trait RoyaltyExtension {
+    /// Whether the extension sets a royalty along with an address to pay it to
+    fn has_payable_royalty(&self) -> bool;
+}
+
+impl RoyaltyExtension for Empty {
+    fn has_payable_royalty(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Deserialize)]
+struct ExtensionTraits {
+    attributes: Option<Vec<ExtensionTrait>>,
+}
+
+#[derive(Deserialize)]
+struct ExtensionTrait {
+    trait_type: String,
+    value: String,
+}
+
+/// The distinct `(trait_type, value)` pairs of an extension following the cw721 metadata
+/// layout. Extensions without string attributes have none.
+pub fn token_traits<T: Serialize>(extension: &T) -> Vec<(String, String)> {
+    let attributes = to_vec(extension)
+        .and_then(|data| from_slice::<Option<ExtensionTraits>>(&data))
+        .ok()
+        .flatten()
+        .and_then(|extension| extension.attributes)
+        .unwrap_or_default();
+    let mut traits: Vec<_> = attributes
+        .into_iter()
+        .map(|attribute| (attribute.trait_type, attribute.value))
+        .collect();
+    traits.sort();
+    traits.dedup();
+    traits
+}
+
+/// A token offered for sale at a fixed price in the rewards denom
+#[cw_serde]
+pub struct Listing {
+    pub seller: Addr,
+    pub price: Coin,
+}
+
+/// Funds in the rewards denom a bidder escrowed for a token
+#[cw_serde]
+pub struct Offer {
+    pub amount: Coin,
+    pub expires: Expiration,
+}
+
+/// An English auction in the rewards denom
+#[cw_serde]
+pub struct Auction {
+    pub seller: Addr,
+    /// The lowest first bid
+    pub reserve: Coin,
+    /// How much each bid must exceed the previous one by
+    pub min_increment: Uint128,
+    pub end: Expiration,
+    pub highest_bid: Option<AuctionBid>,
+}
+
+#[cw_serde]
+pub struct AuctionBid {
+    pub bidder: Addr,
+    pub amount: Uint128,
+}
+
+pub struct ListingIndexes<'a> {
+    pub seller: MultiIndex<'a, Addr, Listing, String>,
+    pub price: MultiIndex<'a, u128, Listing, String>,
+}
+
+impl<'a> IndexList<Listing> for ListingIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Listing>> + '_> {
+        let v: Vec<&dyn Index<Listing>> = vec![&self.seller, &self.price];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn listing_seller_idx(_pk: &[u8], d: &Listing) -> Addr {
+    d.seller.clone()
+}
+
+pub fn listing_price_idx(_pk: &[u8], d: &Listing) -> u128 {
+    d.price.amount.u128()
+}